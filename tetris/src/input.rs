@@ -0,0 +1,150 @@
+use std::time::{Duration, Instant};
+
+/// Delayed auto shift: how long a direction is held before it repeats
+pub const DAS: Duration = Duration::from_millis(170);
+/// Auto repeat rate: time between repeated shifts once DAS has charged
+pub const ARR: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shift {
+    Left,
+    Right,
+}
+
+impl Shift {
+    pub fn dx(&self) -> i32 {
+        match self {
+            Shift::Left => -1,
+            Shift::Right => 1,
+        }
+    }
+}
+
+// A direction being held and how many shifts it has produced so far
+#[derive(Debug, Clone, Copy)]
+struct Held {
+    shift: Shift,
+    since: Instant,
+    shifts: u32,
+}
+
+/// Turns left/right presses and releases into shifts with DAS/ARR timing.
+/// Needs release events from the terminal, without them every press is a
+/// single shift.
+#[derive(Debug, Clone)]
+pub struct AutoShift {
+    pub das: Duration,
+    pub arr: Duration,
+    held: Option<Held>,
+}
+
+impl Default for AutoShift {
+    fn default() -> Self {
+        Self::new(DAS, ARR)
+    }
+}
+
+impl AutoShift {
+    pub fn new(das: Duration, arr: Duration) -> Self {
+        Self {
+            das,
+            arr,
+            held: None,
+        }
+    }
+
+    /// Start holding `shift`, taking over from any other direction. The
+    /// first shift happens straight away.
+    pub fn press(&mut self, shift: Shift, now: Instant) {
+        self.held = Some(Held {
+            shift,
+            since: now,
+            shifts: 0,
+        });
+    }
+
+    pub fn release(&mut self, shift: Shift) {
+        if self.held.is_some_and(|held| held.shift == shift) {
+            self.held = None;
+        }
+    }
+
+    /// Shifts due since the last call, in the held direction. With an ARR
+    /// of zero a charged DAS asks for `u32::MAX`, i.e. all the way to the wall.
+    pub fn update(&mut self, now: Instant) -> Option<(Shift, u32)> {
+        let held = self.held.as_mut()?;
+        let elapsed = now.saturating_duration_since(held.since);
+
+        let due = match elapsed.checked_sub(self.das) {
+            None => 1,
+            Some(_) if self.arr.is_zero() => u32::MAX,
+            Some(charged) => 2 + (charged.as_nanos() / self.arr.as_nanos()) as u32,
+        };
+        let shifts = due.saturating_sub(held.shifts);
+        held.shifts = due;
+        (shifts > 0).then_some((held.shift, shifts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn press_shifts_once_until_das_charges() {
+        let start = Instant::now();
+        let mut auto = AutoShift::new(ms(170), ms(50));
+        auto.press(Shift::Left, start);
+
+        assert_eq!(auto.update(start), Some((Shift::Left, 1)));
+        assert_eq!(auto.update(start + ms(100)), None);
+        assert_eq!(auto.update(start + ms(169)), None);
+        // DAS charged, then one shift every ARR
+        assert_eq!(auto.update(start + ms(170)), Some((Shift::Left, 1)));
+        assert_eq!(auto.update(start + ms(200)), None);
+        assert_eq!(auto.update(start + ms(220)), Some((Shift::Left, 1)));
+        // A slow caller catches up on missed repeats
+        assert_eq!(auto.update(start + ms(370)), Some((Shift::Left, 3)));
+    }
+
+    #[test]
+    fn release_stops_repeating() {
+        let start = Instant::now();
+        let mut auto = AutoShift::new(ms(170), ms(50));
+        auto.press(Shift::Right, start);
+        assert_eq!(auto.update(start), Some((Shift::Right, 1)));
+
+        // Releasing the other direction changes nothing
+        auto.release(Shift::Left);
+        assert_eq!(auto.update(start + ms(170)), Some((Shift::Right, 1)));
+
+        auto.release(Shift::Right);
+        assert_eq!(auto.update(start + ms(1000)), None);
+    }
+
+    #[test]
+    fn new_direction_takes_over() {
+        let start = Instant::now();
+        let mut auto = AutoShift::new(ms(170), ms(50));
+        auto.press(Shift::Left, start);
+        assert_eq!(auto.update(start + ms(300)), Some((Shift::Left, 4)));
+
+        auto.press(Shift::Right, start + ms(300));
+        assert_eq!(auto.update(start + ms(300)), Some((Shift::Right, 1)));
+        assert_eq!(auto.update(start + ms(400)), None);
+    }
+
+    #[test]
+    fn zero_arr_goes_to_the_wall() {
+        let start = Instant::now();
+        let mut auto = AutoShift::new(ms(170), Duration::ZERO);
+        auto.press(Shift::Left, start);
+        assert_eq!(auto.update(start), Some((Shift::Left, 1)));
+        assert_eq!(auto.update(start + ms(170)), Some((Shift::Left, u32::MAX - 1)));
+        assert_eq!(auto.update(start + ms(500)), None);
+    }
+}
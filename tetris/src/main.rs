@@ -1,52 +1,127 @@
-use std::{cmp::min, io::Read, sync::{Arc, Mutex}, thread, time::Duration};
-use crossterm::event::{self, Event, KeyCode};
+mod input;
+mod piece;
+mod randomizer;
+mod render;
+mod scoring;
+
+use std::{
+    fs::File,
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyboardEnhancementFlags},
+    event::{PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    execute,
+    style::{Color, ResetColor},
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use log::{debug, info};
+use pretty_env_logger::env_logger::{Target, WriteStyle};
+use input::{AutoShift, Shift};
+use piece::{Piece, PieceKind, Rotation};
+use randomizer::SevenBag;
+use render::{colour, Frame, Screen};
+use scoring::{Score, TSpin};
 const PX_EMPTY: u8 = 0;
+// How many upcoming pieces are shown beside the board
+const PREVIEW: usize = 5;
+/// How long a piece can rest on the stack before it locks
+const LOCK_DELAY: Duration = Duration::from_millis(500);
+/// Moves and rotations that can restart the lock delay for one piece
+const MAX_LOCK_RESETS: u32 = 15;
+// How often the game loop checks gravity and the lock delay
+const TICK: Duration = Duration::from_millis(10);
+// Columns right of the board for the hold slot, next pieces and score
+const PANEL_WIDTH: usize = 20;
+// The board takes over the terminal, so logging goes here instead
+const LOG_FILE: &str = "tetris.log";
 
 pub trait Drawable: Send {
     fn get_sprite(&self) -> [[u8; 4]; 4];
+    /// Turn a quarter clockwise, or counter-clockwise
+    fn rotate(&mut self, clockwise: bool);
+    /// Offsets (x right, y down) to try, in order, when rotating
+    fn kicks(&self, clockwise: bool) -> [(i32, i32); 5];
 }
 
-#[derive(Default)]
-pub struct FourCube;
-
-impl Drawable for FourCube {
+impl Drawable for Piece {
     fn get_sprite(&self) -> [[u8; 4]; 4] {
-        [[0, 0, 0, 0], [0, 0, 0, 0], [1, 1, 0, 0], [1, 1, 0, 0]]
+        let mut sprite = [[0; 4]; 4];
+        for (x, y) in self.cells() {
+            sprite[y][x] = 1;
+        }
+        sprite
     }
-}
 
-#[derive(Default)]
-pub struct FourBar;
+    fn rotate(&mut self, clockwise: bool) {
+        self.rotation = match clockwise {
+            true => self.rotation.clockwise(),
+            false => self.rotation.counter_clockwise(),
+        };
+    }
 
-impl Drawable for FourBar {
-    fn get_sprite(&self) -> [[u8; 4]; 4] {
-        [[1, 0, 0, 0], 
-        [1, 0, 0, 0], 
-        [1, 0, 0, 0], 
-        [1, 0, 0, 0]]
+    fn kicks(&self, clockwise: bool) -> [(i32, i32); 5] {
+        let to = match clockwise {
+            true => self.rotation.clockwise(),
+            false => self.rotation.counter_clockwise(),
+        };
+        piece::kicks(self.kind, self.rotation, to)
     }
 }
 
+// The piece still under the player's control
+#[derive(Debug, Clone, Copy)]
+struct Falling {
+    piece: Piece,
+    // Top left of the piece's 4x4 pattern. Signed, as wall kicks can hang
+    // the empty part of a pattern off the board
+    location: (i32, i32),
+    // Which kick test the last move used, if that move was a rotation
+    last_kick: Option<usize>,
+    // When it came to rest on the stack, while waiting to lock
+    landed: Option<Instant>,
+    // How many times moving it has restarted the lock delay
+    lock_resets: u32,
+}
 
-#[derive(Default)]
-pub struct TriThing;
-
-impl Drawable for TriThing {
-    fn get_sprite(&self) -> [[u8; 4]; 4] {
-        [[0, 0, 0, 0], 
-        [0, 0, 0, 0], 
-        [0, 1, 0, 0], 
-        [1, 1, 0, 0]]
+impl Falling {
+    fn cells(&self) -> [(i32, i32); 4] {
+        let (x, y) = self.location;
+        self.piece.cells().map(|(px, py)| (x + px as i32, y + py as i32))
     }
 }
 
+/// Whether the game is running, paused, or over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Playing,
+    Paused,
+    /// A new piece spawned overlapping the stack
+    ToppedOut,
+}
+
 pub struct Board {
+    /// Cells of pieces that have landed. The falling piece is kept apart
+    /// and only drawn over them
     pub pixels: Vec<Vec<u8>>,
+    pub score: Score,
+    pub status: Status,
+    /// Pieces locked into the stack so far
+    pub placed: u32,
     width: usize,
     height: usize,
-    sprites: Vec<Box<dyn Drawable>>,
-    sprite_locations: Vec<(usize, usize)>,
-    active: Option<usize>
+    active: Option<Falling>,
+    pieces: SevenBag,
+    // Piece put aside for later, and whether the falling piece already swapped
+    hold: Option<PieceKind>,
+    hold_used: bool,
 }
 
 impl Board {
@@ -56,222 +131,1081 @@ impl Board {
             width,
             height,
             pixels: board,
-            sprites: vec![],
-            sprite_locations: vec![],
-            active: None
+            score: Score::default(),
+            status: Status::Playing,
+            placed: 0,
+            active: None,
+            pieces: SevenBag::new(rand::random()),
+            hold: None,
+            hold_used: false,
         }
     }
 
+    /// Deal pieces from `seed`, so a game can be replayed
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.pieces = SevenBag::new(seed);
+        self
+    }
+
     fn gen_empty_board(width: usize, height: usize) -> Vec<Vec<u8>> {
-        let mut board = Vec::with_capacity(height);
+        vec![vec![PX_EMPTY; width]; height]
+    }
 
+    /// Start `piece` falling from the top middle of the board. If the stack
+    /// is in the way the game is over. Returns whether it spawned.
+    pub fn spawn(&mut self, piece: Piece) -> bool {
+        let init_pos = ((self.width as i32 - 4) / 2, 0);
+        let falling = Falling {
+            piece,
+            location: init_pos,
+            last_kick: None,
+            landed: None,
+            lock_resets: 0,
+        };
+        if !self.fits(&falling) {
+            info!("Topped out with {} points", self.score.points);
+            self.active = None;
+            self.status = Status::ToppedOut;
+            return false;
+        }
+        self.active = Some(falling);
+        true
+    }
 
-        for _ in 0..height {
-            let mut row = Vec::with_capacity(width);
-            for _ in 0..width {
-                row.push(PX_EMPTY);
+    /// Pause a running game, or resume a paused one
+    pub fn toggle_pause(&mut self) {
+        self.status = match self.status {
+            Status::Playing => Status::Paused,
+            Status::Paused => {
+                // Time spent paused doesn't count towards locking
+                if let Some(active) = self.active.as_mut() {
+                    active.landed = None;
+                }
+                Status::Playing
             }
-            board.push(row);
+            Status::ToppedOut => Status::ToppedOut,
+        };
+    }
+
+    /// Start the next piece from the bag falling
+    pub fn spawn_next(&mut self) {
+        let kind = self.pieces.next();
+        debug!("Spawning {:?}", kind);
+        self.spawn(Piece::new(kind));
+        self.hold_used = false;
+    }
+
+    /// Put the falling piece on hold and bring in the one held before, or
+    /// the next from the bag if none was. Only once per piece, returns
+    /// whether it swapped.
+    pub fn hold(&mut self) -> bool {
+        let Some(active) = self.active else {
+            return false;
+        };
+        if self.hold_used {
+            return false;
         }
 
+        let kind = self.hold.replace(active.piece.kind).unwrap_or_else(|| self.pieces.next());
+        debug!("Holding {:?} for {:?}", active.piece.kind, kind);
+        self.spawn(Piece::new(kind));
+        self.hold_used = true;
+        true
+    }
 
+    /// Whether a piece is falling
+    pub fn has_active(&self) -> bool {
+        self.active.is_some()
+    }
 
-        board
+    pub fn get_cell(&self, x: usize, y: usize) -> u8 {
+        self.pixels[y][x]
+    }
+
+    /// Move the active piece `dx` columns if nothing is in the way.
+    /// Returns whether it moved.
+    pub fn shift(&mut self, dx: i32) -> bool {
+        let moved = self.try_move(dx, 0);
+        if moved {
+            self.reset_lock_delay();
+        }
+        moved
     }
 
-    fn clear_board(&mut self ) {
-        for y in 0..self.pixels.len() {
-          
-            for x in 0..self.pixels[y].len() {
-                self.pixels[y][x] = PX_EMPTY;
+    /// Move the active piece down a row if nothing is in the way, scoring a
+    /// point for it. Returns whether it moved.
+    pub fn soft_drop(&mut self) -> bool {
+        let moved = self.try_move(0, 1);
+        if moved {
+            self.score.soft_drop(1);
+        }
+        moved
+    }
+
+    /// Drop the active piece as far as it goes and lock it there. Returns
+    /// how many rows it fell.
+    pub fn hard_drop(&mut self) -> u32 {
+        let mut rows = 0;
+        while self.try_move(0, 1) {
+            rows += 1;
+        }
+        self.score.hard_drop(rows);
+        self.lock();
+        rows
+    }
+
+    fn try_move(&mut self, dx: i32, dy: i32) -> bool {
+        let Some(active) = self.active else {
+            return false;
+        };
+
+        let mut moved = active;
+        moved.location = (active.location.0 + dx, active.location.1 + dy);
+        moved.last_kick = None;
+        if !self.fits(&moved) {
+            return false;
+        }
+        // Off the stack again, the lock delay starts over when it next lands
+        if dy > 0 {
+            moved.landed = None;
+        }
+        self.active = Some(moved);
+        true
+    }
+
+    // Moving or turning a resting piece gives it a fresh lock delay, up to
+    // `MAX_LOCK_RESETS` times, so it can't be kept from locking forever
+    fn reset_lock_delay(&mut self) {
+        if let Some(active) = self.active.as_mut() {
+            if active.landed.is_some() && active.lock_resets < MAX_LOCK_RESETS {
+                active.landed = None;
+                active.lock_resets += 1;
+            }
+        }
+    }
+
+    /// Rotate the active piece, trying the Super Rotation System wall kicks
+    /// in order until one leaves it inside the board and off the stack.
+    /// Returns whether it turned.
+    pub fn rotate(&mut self, clockwise: bool) -> bool {
+        let Some(active) = self.active else {
+            return false;
+        };
+
+        let mut turned = active;
+        turned.piece.rotate(clockwise);
+        for (kick, (dx, dy)) in active.piece.kicks(clockwise).into_iter().enumerate() {
+            turned.location = (active.location.0 + dx, active.location.1 + dy);
+            turned.last_kick = Some(kick);
+            if self.fits(&turned) {
+                self.active = Some(turned);
+                self.reset_lock_delay();
+                return true;
             }
-          
         }
+        false
+    }
 
+    // Whether `falling` lies inside the board on empty cells
+    fn fits(&self, falling: &Falling) -> bool {
+        falling.cells().into_iter().all(|(x, y)| self.is_free(x, y))
+    }
 
+    fn is_free(&self, x: i32, y: i32) -> bool {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return false;
+        }
+        self.pixels[y as usize][x as usize] == PX_EMPTY
     }
 
-    pub fn new_sprite(&mut self, sprite: Box<dyn Drawable>) {
-        let init_pos = (self.width / 2, 0);
-        self.sprites.push(sprite);
+    // Three-corner rule: a T whose last move was a rotation, with at least
+    // three of the corners around its centre filled. It is a full T-spin if
+    // both corners it points at are filled, or it got there with the last kick.
+    fn t_spin(&self, falling: &Falling) -> Option<TSpin> {
+        if falling.piece.kind != PieceKind::T {
+            return None;
+        }
+        let kick = falling.last_kick?;
 
-        let idx = self.sprites.len() - 1;
+        let (cx, cy) = (falling.location.0 + 1, falling.location.1 + 1);
+        // Clockwise from top left, so the pair a rotation points at is adjacent
+        let corners = [(-1, -1), (1, -1), (1, 1), (-1, 1)]
+            .map(|(dx, dy)| !self.is_free(cx + dx, cy + dy));
+        if corners.iter().filter(|&&filled| filled).count() < 3 {
+            return None;
+        }
 
-        self.sprite_locations.insert(idx, init_pos);
-        self.active = Some(idx);
+        let front = match falling.piece.rotation {
+            Rotation::Spawn => [0, 1],
+            Rotation::Right => [1, 2],
+            Rotation::Two => [2, 3],
+            Rotation::Left => [3, 0],
+        };
+        match front.iter().all(|&corner| corners[corner]) || kick == 4 {
+            true => Some(TSpin::Full),
+            false => Some(TSpin::Mini),
+        }
     }
-    pub fn get_cell(&self, x: usize, y: usize) -> u8 {
-        return self.pixels[y][x];
+
+    /// Drop the active piece a row if nothing is in the way. Returns
+    /// whether it moved.
+    pub fn fall(&mut self) -> bool {
+        self.try_move(0, 1)
     }
 
-    pub fn input(&mut self, dir_right: bool) {
-        if let Some(active_idx) = self.active{
-            let pos = &mut self.sprite_locations[active_idx];
-            pos.0 = pos.0 + match dir_right { true => 1, false => 0}
+    /// Start the lock delay of an active piece resting on the stack, and
+    /// lock it once the delay has run out at `now`. Nothing locks while paused.
+    pub fn update(&mut self, now: Instant) {
+        let Some(active) = self.active else {
+            return;
+        };
+        if self.status != Status::Playing {
+            return;
+        }
+        let mut below = active;
+        below.location.1 += 1;
+        let landed = match (self.fits(&below), active.landed) {
+            (true, _) => None,
+            (false, None) => Some(now),
+            (false, Some(landed)) if now.saturating_duration_since(landed) >= LOCK_DELAY => {
+                return self.lock();
+            }
+            (false, landed) => landed,
+        };
+        if let Some(active) = self.active.as_mut() {
+            active.landed = landed;
         }
     }
-    fn move_sprite(&mut self) {
-        let board = &self.pixels;
-        for (idx, sprite_pos) in self.sprite_locations.iter_mut().enumerate() {
-            let sprite = &mut self.sprites[idx];
 
-            let our_key = u8::MAX - idx as u8;
-            let (current_x, current_y) = *sprite_pos;
-            
-            let mut board: Vec<Vec<u8>> = board.clone();
-            for y in 0..self.pixels.len(){
-                for x in 0..self.pixels[y].len(){
-                   if board[y][x] == our_key{
-                        board[y][x] = 0;
-                   }
-                }
+    // Where the active piece would land if hard dropped
+    fn ghost(&self) -> Option<Falling> {
+        let mut ghost = self.active?;
+        loop {
+            ghost.location.1 += 1;
+            if !self.fits(&ghost) {
+                ghost.location.1 -= 1;
+                return Some(ghost);
+            }
+        }
+    }
+
+    // Bake the active piece into the stack and clear any rows it completes
+    fn lock(&mut self) {
+        let Some(active) = self.active.take() else {
+            return;
+        };
+        let t_spin = self.t_spin(&active);
+
+        for (x, y) in active.cells() {
+            if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+                self.pixels[y as usize][x as usize] = active.piece.kind.cell();
             }
+        }
+        debug!("Locked {:?} at {:?}", active.piece.kind, active.location);
+        self.placed += 1;
 
-            // Check the lowest row for pixels (x range)
+        let lines = self.clear_lines();
+        if let Some(award) = self.score.lock(lines, t_spin) {
+            info!("{}", award);
+        }
+    }
 
-            let mut move_valid = true;
+    /// Remove full rows from the stack, dropping everything above them.
+    /// Returns how many rows went.
+    fn clear_lines(&mut self) -> usize {
+        let before = self.pixels.len();
+        self.pixels.retain(|row| row.contains(&PX_EMPTY));
+        let cleared = before - self.pixels.len();
+        for _ in 0..cleared {
+            self.pixels.insert(0, vec![PX_EMPTY; self.width]);
+        }
+        cleared
+    }
 
-            let pattern = sprite.get_sprite();
-            let mut desired_y = min(self.height, current_y + 1);
-            for pattern_y in 0..4 {
-                for pattern_x in 0..4 {
-                    let now_x = min(self.width -1, pattern_x + current_x);
-                    let now_y = min(self.height -1, pattern_y + current_y);
+    /// The board with the hold slot and next pieces beside it, and the
+    /// score underneath
+    pub fn draw(&self) -> Frame {
+        let board_width = 2 * self.width + 4;
+        let mut frame = Frame::new(board_width + PANEL_WIDTH, self.height + 4);
 
-                    let future_x = min(self.width -1, now_x);
-                    let future_y = min(self.height-1 , now_y + 1);
+        let border = format!("||{}||", "==".repeat(self.width));
+        frame.print(0, 0, &border, Color::Reset);
+        frame.print(0, self.height + 1, &border, Color::Reset);
 
-                    let current_val = pattern[pattern_y][pattern_x];
-                    let future_val = board[future_y][future_x];
+        let active = self.active.map(|active| (active.cells(), active.piece.kind));
+        let ghost = self.ghost().map(|ghost| ghost.cells()).unwrap_or_default();
+        for (y, row) in self.pixels.iter().enumerate() {
+            frame.print(0, y + 1, "||", Color::Reset);
+            frame.print(board_width - 2, y + 1, "||", Color::Reset);
+            for (x, &cell) in row.iter().enumerate() {
+                let at = (x as i32, y as i32);
+                let (text, colour) = match active {
+                    Some((cells, kind)) if cells.contains(&at) => ("[]", colour(kind)),
+                    // Outline of where the piece lands, under the piece itself
+                    Some((_, kind)) if ghost.contains(&at) => ("::", colour(kind)),
+                    _ if cell == PX_EMPTY => continue,
+                    _ => ("[]", PieceKind::from_cell(cell).map_or(Color::DarkGrey, colour)),
+                };
+                frame.print(2 + 2 * x, y + 1, text, colour);
+            }
+        }
 
-                    if current_val != 0 && (future_val != 0 ) {
-                       move_valid = false;
-                       println!("COLLISION: {}.{} with {}", future_x, future_y, future_val);
-                    }
+        let panel = board_width + 2;
+        frame.print(panel, 1, "Hold", Color::Reset);
+        if let Some(kind) = self.hold {
+            // Greyed out until the next piece, as it can't be swapped back yet
+            let shade = match self.hold_used {
+                true => Color::DarkGrey,
+                false => colour(kind),
+            };
+            draw_preview(&mut frame, (panel, 2), kind, shade);
+        }
+        frame.print(panel, 5, "Next", Color::Reset);
+        for (i, kind) in self.pieces.preview(PREVIEW).enumerate() {
+            draw_preview(&mut frame, (panel, 6 + 3 * i), kind, colour(kind));
+        }
+
+        let score = format!(
+            "Score: {}  Lines: {}  Level: {}",
+            self.score.points,
+            self.score.lines,
+            self.score.level()
+        );
+        frame.print(0, self.height + 2, &score, Color::Reset);
+        if let Some(award) = self.score.last_award {
+            frame.print(0, self.height + 3, &award.to_string(), Color::Reset);
+        }
+
+        match self.status {
+            Status::Playing => {}
+            // Hide the stack so a pause can't be used to plan ahead
+            Status::Paused => {
+                let blank = " ".repeat(2 * self.width);
+                for y in 0..self.height {
+                    frame.print(2, y + 1, &blank, Color::Reset);
+                }
+                self.draw_message(&mut frame, &["PAUSED", "", "p: resume"]);
+            }
+            Status::ToppedOut => {
+                let stats = [
+                    "GAME OVER".to_string(),
+                    String::new(),
+                    format!("Score  {}", self.score.points),
+                    format!("Lines  {}", self.score.lines),
+                    format!("Level  {}", self.score.level()),
+                    format!("Pieces {}", self.placed),
+                    String::new(),
+                    "r: restart".to_string(),
+                    "q: quit".to_string(),
+                ];
+                self.draw_message(&mut frame, &stats);
+            }
+        }
+        frame
+    }
+
+    // Lines of text centred on the board, over whatever is there
+    fn draw_message(&self, frame: &mut Frame, lines: &[impl AsRef<str>]) {
+        let top = self.height.saturating_sub(lines.len()) / 2 + 1;
+        for (y, line) in lines.iter().enumerate() {
+            let line = format!("{:^width$}", line.as_ref(), width = 2 * self.width);
+            frame.print(2, top + y, &line, Color::Reset);
+        }
+    }
+}
+
+// A piece in spawn orientation fits in two rows
+fn draw_preview(frame: &mut Frame, (x, y): (usize, usize), kind: PieceKind, colour: Color) {
+    for (px, py) in Piece::new(kind).cells() {
+        frame.print(x + 2 * px, y + py, "[]", colour);
+    }
+}
+
+// Draw the board, writing only what changed since it was last shown
+fn show(board: &Board, screen: &Mutex<Screen>) -> io::Result<()> {
+    let frame = board.draw();
+    screen.lock().unwrap().show(&mut io::stdout().lock(), frame)
+}
+
+// Read keys until the player quits, moving the active piece
+fn handle_input(
+    board: &Mutex<Board>,
+    screen: &Mutex<Screen>,
+    running: &AtomicBool,
+    releases: bool,
+) -> io::Result<()> {
+    let mut auto_shift = AutoShift::default();
+
+    while running.load(Ordering::Relaxed) {
+        if event::poll(Duration::from_millis(1))? {
+            let event = event::read()?;
+            if let Event::Resize(..) = event {
+                screen.lock().unwrap().invalidate();
+                show(&board.lock().unwrap(), screen)?;
+            }
+            if let Event::Key(key) = event {
+                let shift = match key.code {
+                    KeyCode::Char('a') | KeyCode::Left => Some(Shift::Left),
+                    KeyCode::Char('d') | KeyCode::Right => Some(Shift::Right),
+                    _ => None,
+                };
 
-                    if future_y == self.height {
-                        move_valid = false;
+                let mut board = board.lock().unwrap();
+                match (key.kind, shift) {
+                    (KeyEventKind::Release, Some(shift)) => auto_shift.release(shift),
+                    (KeyEventKind::Release, None) => {}
+                    // Held directions repeat on DAS/ARR timing, not the terminal's
+                    (KeyEventKind::Repeat, Some(_)) => {}
+                    (_, Some(shift)) if board.status == Status::Playing => {
+                        auto_shift.press(shift, Instant::now())
                     }
+                    (_, Some(_)) => {}
+                    (_, None) => match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => running.store(false, Ordering::Relaxed),
+                        KeyCode::Char('p') => board.toggle_pause(),
+                        KeyCode::Char('r') => {
+                            let seed = rand::random();
+                            info!("Restarting with seed {}", seed);
+                            *board = Board::new(board.width, board.height).with_seed(seed);
+                        }
+                        _ if board.status != Status::Playing => {}
+                        KeyCode::Char('s') | KeyCode::Down => {
+                            board.soft_drop();
+                        }
+                        KeyCode::Char(' ') => {
+                            board.hard_drop();
+                        }
+                        KeyCode::Char('x') | KeyCode::Up => {
+                            board.rotate(true);
+                        }
+                        KeyCode::Char('z') => {
+                            board.rotate(false);
+                        }
+                        KeyCode::Char('c') => {
+                            board.hold();
+                        }
+                        _ => {}
+                    },
                 }
+                show(&board, screen)?;
             }
+        }
 
-            if move_valid {
-                sprite_pos.1 = desired_y
+        if let Some((shift, count)) = auto_shift.update(Instant::now()) {
+            let mut board = board.lock().unwrap();
+            for _ in 0..count {
+                if board.status != Status::Playing || !board.shift(shift.dx()) {
+                    break;
+                }
             }
+            show(&board, screen)?;
+        }
+        // Without release events a held key can't be told apart from a tap
+        if !releases {
+            auto_shift.release(Shift::Left);
+            auto_shift.release(Shift::Right);
         }
     }
+    Ok(())
+}
 
-    fn draw_sprite(&mut self) {
-        
-        for (idx, sprite) in self.sprites.iter().enumerate() {
-            let pattern = sprite.get_sprite();
-            let our_key = u8::MAX - idx as u8;
-            let (x, y) = self.sprite_locations[idx];
+fn main() -> Result<(), anyhow::Error> {
+    // An optional seed as the first argument replays the same pieces
+    let seed = match std::env::args().nth(1) {
+        Some(seed) => seed.parse()?,
+        None => rand::random(),
+    };
+    let log = File::create(LOG_FILE)?;
+    pretty_env_logger::formatted_builder()
+        .parse_filters(&std::env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string()))
+        .target(Target::Pipe(Box::new(log)))
+        .write_style(WriteStyle::Never)
+        .init();
+    info!("Seed {}", seed);
+
+    let board = Mutex::new(Board::new(10, 20).with_seed(seed));
+    let screen = Mutex::new(Screen::default());
+    let running = AtomicBool::new(true);
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, cursor::Hide)?;
+    // Release events, where the terminal can report them, let DAS/ARR see held keys
+    let releases = terminal::supports_keyboard_enhancement().unwrap_or(false);
+    if releases {
+        let flags = KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+        execute!(io::stdout(), PushKeyboardEnhancementFlags(flags))?;
+    }
 
-            // apply the pattern
+    let result = thread::scope(|s| {
+        let input = s.spawn(|| handle_input(&board, &screen, &running, releases));
 
-            for pattern_y in 0..4 {
-                for pattern_x in 0..4 {
-                    let draw_x = min(self.width - 1, pattern_x + x);
-                    let draw_y = min(self.height - 1, pattern_y + y);
-                    self.pixels[draw_y][draw_x] = match pattern[pattern_y][pattern_x] {
-                        1 => {our_key},
-                        _ => {0},
-                    };
+        let mut shown = Ok(());
+        let mut next_fall = Instant::now();
+        while shown.is_ok() && running.load(Ordering::Relaxed) && !input.is_finished() {
+            // Only hold the board while updating so input can get at it between ticks
+            {
+                let mut board = board.lock().unwrap();
+                let now = Instant::now();
+                if board.status != Status::Playing {
+                    // Resuming waits a whole fall before the piece drops
+                    next_fall = now + board.score.gravity();
+                } else if !board.has_active() {
+                    board.spawn_next();
+                    next_fall = now + board.score.gravity();
+                } else if now >= next_fall {
+                    board.fall();
+                    next_fall = now + board.score.gravity();
                 }
+                board.update(now);
+                shown = show(&board, &screen);
             }
+
+            std::thread::sleep(TICK);
         }
+        running.store(false, Ordering::Relaxed);
+        let input_result = input.join().expect("input thread panicked");
+        shown.and(input_result)
+    });
+
+    if releases {
+        execute!(io::stdout(), PopKeyboardEnhancementFlags)?;
     }
-    pub fn print(&mut self) {
-        clearscreen::clear().expect("failed to clear screen");
-        self.clear_board();
-        self.draw_sprite();
-        self.move_sprite();
-
-        print!("||");
-        for x in 0..self.width {
-            print!("==");
-        }
-        print!("||");
-        println!("|");
-        for y in 0..self.pixels.len() {
-            // Row drawing
-            print!("||");
-            for x in 0..self.pixels[y].len(){
-                let val = self.pixels[y][x];
-                let to_print = match val {
-                    1 => "**",
-                    PX_EMPTY => "  ",
-                    _ => "[]",
+    execute!(io::stdout(), ResetColor, cursor::Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scoring::LineClear;
+
+    fn board_with(kind: PieceKind, at: (i32, i32)) -> Board {
+        let mut board = Board::new(10, 20);
+        board.spawn(Piece::new(kind));
+        move_to(&mut board, at);
+        board
+    }
+
+    fn move_to(board: &mut Board, at: (i32, i32)) {
+        board.active.as_mut().unwrap().location = at;
+    }
+
+    fn location(board: &Board) -> (i32, i32) {
+        board.active.unwrap().location
+    }
+
+    fn active_fits(board: &Board) -> bool {
+        board.fits(&board.active.unwrap())
+    }
+
+    // Let the active piece fall onto the stack and sit out its lock delay
+    fn settle(board: &mut Board) {
+        while board.fall() {}
+        let now = Instant::now();
+        board.update(now);
+        board.update(now + LOCK_DELAY);
+    }
+
+    #[test]
+    fn rotates_both_ways_on_an_open_board() {
+        for kind in PieceKind::ALL {
+            let mut board = board_with(kind, (3, 5));
+            for _ in 0..4 {
+                assert!(board.rotate(true), "{:?}", kind);
+            }
+            assert!(board.rotate(false), "{:?}", kind);
+            assert!(board.rotate(false), "{:?}", kind);
+            let mut expected = Piece::new(kind);
+            expected.rotation = Rotation::Two;
+            assert_eq!(board.active.unwrap().piece.get_sprite(), expected.get_sprite());
+        }
+    }
+
+    // Fill cells from rows of '#' (stack) and '.' (empty), starting at row `top`
+    fn stack(board: &mut Board, top: usize, rows: &[&str]) {
+        for (dy, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                board.pixels[top + dy][x] = match c {
+                    '#' => 1,
+                    _ => PX_EMPTY,
                 };
-                print!("{}", to_print);
             }
-            print!("||");
-            println!("|");
         }
+    }
+
+    fn rotation(board: &Board) -> [[u8; 4]; 4] {
+        board.active.unwrap().piece.get_sprite()
+    }
+
+    fn turned(kind: PieceKind, rotation: Rotation) -> [[u8; 4]; 4] {
+        Piece { kind, rotation }.get_sprite()
+    }
+
+    #[test]
+    fn i_kicks_off_the_right_wall() {
+        // Vertical I in the last column: turning flat needs the second I test, (-1, 0)
+        let mut board = board_with(PieceKind::I, (6, 5));
+        assert!(board.rotate(true));
+        move_to(&mut board, (7, 5));
+        assert!(active_fits(&board));
+
+        assert!(board.rotate(true));
+        assert_eq!(rotation(&board), turned(PieceKind::I, Rotation::Two));
+        assert_eq!(location(&board), (6, 5));
+    }
+
+    #[test]
+    fn i_kicks_off_the_left_wall() {
+        // Vertical I (state L) in the first column, its pattern hanging off the board
+        let mut board = board_with(PieceKind::I, (0, 5));
+        assert!(board.rotate(false));
+        move_to(&mut board, (-1, 5));
+        assert!(active_fits(&board));
+
+        // L->0 base position would poke out at x = -1, the second test (+1, 0) fits
+        assert!(board.rotate(true));
+        assert_eq!(rotation(&board), turned(PieceKind::I, Rotation::Spawn));
+        assert_eq!(location(&board), (0, 5));
+    }
+
+    #[test]
+    fn t_kicks_down_into_a_slot() {
+        // T spawned at (3, 5) covers (4,5) (3,6) (4,6) (5,6). The stack blocks the
+        // first four 0->R tests, only the fifth, (-1, +2), drops it into the slot
+        let mut board = board_with(PieceKind::T, (3, 5));
+        stack(&mut board, 5, &[
+            "...#......",
+            "..........",
+            "....######",
+            "###..#####",
+            "###.######",
+        ]);
+        assert!(active_fits(&board));
+
+        assert!(board.rotate(true));
+        assert_eq!(rotation(&board), turned(PieceKind::T, Rotation::Right));
+        assert_eq!(location(&board), (2, 7));
+    }
 
-        print!("||");
-        for x in 0..self.width {
-            print!("==");
+    #[test]
+    fn rotation_is_refused_when_every_kick_is_blocked() {
+        let mut board = board_with(PieceKind::T, (3, 5));
+        // Walled in on every side, so no kick has anywhere to go
+        stack(&mut board, 2, &["##########"; 10]);
+        stack(&mut board, 5, &["####.#####", "###...####"]);
+        assert!(active_fits(&board));
+
+        for clockwise in [true, false] {
+            assert!(!board.rotate(clockwise));
+            assert_eq!(rotation(&board), turned(PieceKind::T, Rotation::Spawn));
+            assert_eq!(location(&board), (3, 5));
         }
-        print!("||");
-        println!("|");
     }
-}
 
-fn random_peice() -> Box<dyn Drawable>{
+    #[test]
+    fn o_does_not_kick() {
+        let mut board = board_with(PieceKind::O, (7, 18));
+        assert!(active_fits(&board));
+        assert!(board.rotate(true));
+        assert!(board.rotate(false));
+        assert_eq!(location(&board), (7, 18));
+    }
 
-    let choice: u8 = rand::random();
+    #[test]
+    fn full_rows_are_cleared_and_the_rest_drops() {
+        let mut board = Board::new(4, 6);
+        board.pixels = vec![
+            vec![0, 0, 0, 0],
+            vec![0, 1, 0, 0],
+            vec![1, 1, 1, 1],
+            vec![1, 0, 0, 1],
+            vec![1, 1, 1, 1],
+            vec![0, 1, 1, 0],
+        ];
+        assert_eq!(board.clear_lines(), 2);
+        assert_eq!(
+            board.pixels,
+            vec![
+                vec![0, 0, 0, 0],
+                vec![0, 0, 0, 0],
+                vec![0, 0, 0, 0],
+                vec![0, 1, 0, 0],
+                vec![1, 0, 0, 1],
+                vec![0, 1, 1, 0],
+            ]
+        );
+        assert_eq!(board.clear_lines(), 0);
+    }
 
+    #[test]
+    fn landing_in_a_well_scores_a_tetris() {
+        let mut board = board_with(PieceKind::I, (0, 0));
+        // Vertical I (state L) over an empty first column
+        assert!(board.rotate(false));
+        move_to(&mut board, (-1, 0));
+        for row in board.pixels.iter_mut().skip(16) {
+            row[1..].fill(PieceKind::J.cell());
+        }
+
+        settle(&mut board);
 
-    match choice{
-        0..40 =>{ Box::new(FourCube::default())},
-        100..200 =>{ Box::new(TriThing::default())}
-        _ => { Box::new(FourBar::default())},
+        assert!(!board.has_active());
+        assert!(board.pixels.iter().flatten().all(|&cell| cell == PX_EMPTY));
+        assert_eq!(board.score.last_clear, Some(LineClear::Tetris));
+        assert_eq!((board.score.points, board.score.lines), (800, 4));
     }
 
-}
-fn main() {
-    println!("Hello, world!");
-
-    let board = Arc::new(Mutex::new( Board::new(10, 20)));
-
-    thread::scope(|s| {
-        let board_clone = board.clone();
-        s.spawn(move || {
-        
-            loop {
-                if event::poll(Duration::from_millis(1)).unwrap() {
-                    if let Event::Key(key_event) = event::read().unwrap() {
-                        match key_event.code {
-                            KeyCode::Char('a') => {
-                                let mut board = board_clone.lock().unwrap();
-                                board.input(false);
-                                println!("MOVING LEFT");
-                            }
-                            KeyCode::Char('d') => {
-                                let mut board = board_clone.lock().unwrap();
-                                board.input(true);
-                                println!("MOVING RIGHT");
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-                
-            }
-        });
-        let mut board = board.lock().unwrap();
-        for t in 0..100 {
-            board.print();
-
-            if t % 10 == 0 {
-                println!("Spawning new piece");
-                board.new_sprite(random_peice());
+    #[test]
+    fn partial_rows_stay_after_a_lock() {
+        let mut board = board_with(PieceKind::O, (-1, 0));
+        for row in board.pixels.iter_mut().skip(18) {
+            row[2..].fill(PieceKind::J.cell());
+        }
+        // Second row from the bottom has a hole the O can't reach
+        board.pixels[18][5] = PX_EMPTY;
+
+        settle(&mut board);
+
+        assert_eq!(board.score.last_clear, Some(LineClear::Single));
+        assert_eq!(board.score.points, 100);
+        let o = PieceKind::O.cell();
+        assert_eq!(board.pixels[19][..2], [o, o]);
+        assert_eq!(board.pixels[19][5], PX_EMPTY);
+        assert_eq!(board.pixels[19].iter().filter(|&&c| c != PX_EMPTY).count(), 9);
+    }
+
+    #[test]
+    fn hundreds_of_pieces_lock_into_the_stack() {
+        // Five O pieces side by side make a double, well past 255 pieces in all
+        let mut board = Board::new(10, 20);
+        for n in 0..300 {
+            for column in 0..5 {
+                board.spawn(Piece::new(PieceKind::O));
+                move_to(&mut board, (column * 2 - 1, 0));
+                settle(&mut board);
             }
+            assert_eq!(board.score.lines, 2 * (n + 1));
+        }
+        assert!(board.pixels.iter().flatten().all(|&cell| cell == PX_EMPTY));
+    }
+
+    #[test]
+    fn shifting_stops_at_the_walls() {
+        let mut board = board_with(PieceKind::T, (3, 0));
+        while board.shift(-1) {}
+        assert_eq!(location(&board), (0, 0));
+        while board.shift(1) {}
+        assert_eq!(location(&board), (7, 0));
+
+        // Vertical I hangs its empty columns past the wall
+        let mut board = board_with(PieceKind::I, (3, 0));
+        assert!(board.rotate(true));
+        while board.shift(-1) {}
+        assert_eq!(location(&board), (-2, 0));
+        while board.shift(1) {}
+        assert_eq!(location(&board), (7, 0));
+    }
 
-            std::thread::sleep(Duration::from_secs_f64(0.5));
+    #[test]
+    fn shifting_stops_at_the_stack() {
+        let mut board = board_with(PieceKind::O, (3, 16));
+        stack(&mut board, 16, &["#.......#.", "##......##"]);
+        while board.shift(-1) {}
+        assert_eq!(location(&board), (1, 16));
+        while board.shift(1) {}
+        assert_eq!(location(&board), (5, 16));
+    }
+
+    #[test]
+    fn soft_drop_never_locks() {
+        let mut board = board_with(PieceKind::O, (3, 0));
+        let mut rows = 0;
+        while board.soft_drop() {
+            rows += 1;
         }
-    });
+        assert_eq!(rows, 18);
+        assert!(board.has_active());
+        assert!(board.pixels.iter().flatten().all(|&cell| cell == PX_EMPTY));
+    }
+
+    #[test]
+    fn hard_drop_lands_on_the_stack_and_locks() {
+        let mut board = board_with(PieceKind::T, (3, 0));
+        stack(&mut board, 15, &["....#....."]);
+        assert_eq!(board.hard_drop(), 13);
+        assert!(!board.has_active());
+        let filled: Vec<(usize, usize)> = (0..20)
+            .flat_map(|y| (0..10).map(move |x| (x, y)))
+            .filter(|&(x, y)| board.pixels[y][x] != PX_EMPTY)
+            .collect();
+        assert_eq!(filled, [(4, 13), (3, 14), (4, 14), (5, 14), (4, 15)]);
+    }
+
+    #[test]
+    fn t_spin_double_from_the_last_kick() {
+        // Same slot as `t_kicks_down_into_a_slot`, the fifth kick always makes a full T-spin
+        let mut board = board_with(PieceKind::T, (3, 5));
+        stack(&mut board, 5, &[
+            "...#......",
+            "..........",
+            "....######",
+            "###..#####",
+            "###.######",
+        ]);
+        assert!(board.rotate(true));
+        assert_eq!(board.hard_drop(), 0);
 
+        let award = board.score.last_award.unwrap();
+        assert_eq!(award.t_spin, Some(TSpin::Full));
+        assert_eq!(award.clear, Some(LineClear::Double));
+        assert_eq!(board.score.points, 1200);
+    }
+
+    #[test]
+    fn t_spin_single_under_an_overhang() {
+        let mut board = board_with(PieceKind::T, (3, 17));
+        stack(&mut board, 17, &["...#......", "##....####", "####.#####"]);
+        assert!(board.rotate(true));
+        assert!(board.rotate(true));
+        assert_eq!(location(&board), (3, 17));
+        board.hard_drop();
+
+        let award = board.score.last_award.unwrap();
+        assert_eq!(award.t_spin, Some(TSpin::Full));
+        assert_eq!(award.clear, Some(LineClear::Single));
+        assert_eq!(award.points, 800);
+    }
+
+    #[test]
+    fn mini_t_spin_against_the_wall() {
+        // Kicked into the wall with only one corner it points at filled
+        let mut board = board_with(PieceKind::T, (0, 17));
+        stack(&mut board, 17, &["..........", "...#######", ".#########"]);
+        assert!(board.rotate(true));
+        assert_eq!(location(&board), (-1, 17));
+        board.hard_drop();
+
+        let award = board.score.last_award.unwrap();
+        assert_eq!(award.t_spin, Some(TSpin::Mini));
+        assert_eq!(award.clear, Some(LineClear::Single));
+        assert_eq!(award.points, 200);
+    }
+
+    #[test]
+    fn no_t_spin_without_a_rotation_last() {
+        // Three corners filled, but the T slid in under the overhang
+        let mut board = board_with(PieceKind::T, (2, 17));
+        stack(&mut board, 17, &[".#........", "#....#####", ".#.#######"]);
+        assert!(board.shift(-1));
+        assert!(!board.shift(-1));
+        assert_eq!(board.hard_drop(), 0);
+
+        assert_eq!(board.score.last_award, None);
+        assert_eq!(board.score.points, 0);
+    }
+
+    #[test]
+    fn no_active_piece_does_not_rotate() {
+        let mut board = Board::new(10, 20);
+        assert!(!board.rotate(true));
+    }
+
+    #[test]
+    fn draw_colours_cells_by_piece() {
+        let mut board = board_with(PieceKind::T, (3, 5));
+        stack(&mut board, 19, &["##........"]);
+        board.pixels[19][1] = PieceKind::Z.cell();
+        let frame = board.draw();
+
+        // Board cells are two columns wide, inside a two column border
+        let cell = |x: usize, y: usize| frame.get(2 + 2 * x, 1 + y);
+        assert_eq!(cell(4, 5).ch, '[');
+        assert_eq!(cell(4, 5).colour, colour(PieceKind::T));
+        assert_eq!(cell(0, 19).colour, colour(PieceKind::I));
+        assert_eq!(cell(1, 19).colour, colour(PieceKind::Z));
+        assert_eq!(cell(2, 19).ch, ' ');
+    }
+
+    #[test]
+    fn landed_pieces_wait_out_the_lock_delay() {
+        let mut board = board_with(PieceKind::O, (3, 0));
+        while board.fall() {}
+        assert!(!board.fall());
+
+        let landed = Instant::now();
+        board.update(landed);
+        board.update(landed + LOCK_DELAY - Duration::from_millis(1));
+        assert!(board.has_active());
+        board.update(landed + LOCK_DELAY);
+        assert!(!board.has_active());
+        assert_eq!(board.pixels[19][4..6], [PieceKind::O.cell(); 2]);
+    }
+
+    #[test]
+    fn moving_restarts_the_lock_delay() {
+        let mut board = board_with(PieceKind::T, (3, 0));
+        while board.fall() {}
+        let mut now = Instant::now();
+        board.update(now);
+
+        for _ in 0..MAX_LOCK_RESETS {
+            now += LOCK_DELAY / 2;
+            let dx = if board.shift(1) { 1 } else { -1 };
+            assert!(board.shift(-dx) || board.rotate(true));
+            board.update(now);
+            now += LOCK_DELAY - Duration::from_millis(1);
+            board.update(now);
+            assert!(board.has_active());
+        }
+
+        // Out of resets, so the clock keeps running from the last one
+        now += Duration::from_millis(1);
+        assert!(board.shift(-1));
+        board.update(now);
+        assert!(!board.has_active());
+    }
+
+    #[test]
+    fn sliding_off_a_ledge_cancels_the_lock_delay() {
+        let mut board = board_with(PieceKind::O, (3, 16));
+        stack(&mut board, 18, &["...##.....", "...##....."]);
+        let now = Instant::now();
+        board.update(now);
+        assert!(board.active.unwrap().landed.is_some());
+
+        // Nothing below it anymore
+        assert!(board.shift(2));
+        board.update(now + LOCK_DELAY);
+        assert!(board.has_active());
+        assert!(board.fall());
+    }
+
+    #[test]
+    fn ghost_sits_where_a_hard_drop_lands() {
+        let mut board = board_with(PieceKind::T, (3, 0));
+        stack(&mut board, 15, &["....#....."]);
+        assert_eq!(board.ghost().unwrap().location, (3, 13));
+
+        let frame = board.draw();
+        let cell = |x: usize, y: usize| frame.get(2 + 2 * x, 1 + y);
+        assert_eq!(cell(4, 13).ch, ':');
+        assert_eq!(cell(4, 13).colour, colour(PieceKind::T));
+        assert_eq!(cell(4, 0).ch, '[');
+
+        board.hard_drop();
+        assert_eq!(board.pixels[13][4], PieceKind::T.cell());
+    }
+
+    #[test]
+    fn spawning_into_the_stack_tops_out() {
+        let mut board = Board::new(10, 20);
+        stack(&mut board, 0, &["....#....."]);
+        assert!(!board.spawn(Piece::new(PieceKind::O)));
+        assert_eq!(board.status, Status::ToppedOut);
+        assert!(!board.has_active());
+
+        // Pausing does nothing once the game is over
+        board.toggle_pause();
+        assert_eq!(board.status, Status::ToppedOut);
+    }
+
+    #[test]
+    fn stacking_to_the_top_ends_the_game() {
+        let mut board = Board::new(10, 20).with_seed(11);
+        while board.status == Status::Playing {
+            board.spawn_next();
+            board.hard_drop();
+        }
+        assert!(board.placed > 0);
+        assert!(board.pixels[0].iter().chain(&board.pixels[1]).any(|&cell| cell != PX_EMPTY));
+
+        let frame = board.draw();
+        let text: String = (0..22)
+            .flat_map(|y| (0..24).map(move |x| (x, y)))
+            .map(|(x, y)| frame.get(x, y).ch)
+            .collect();
+        assert!(text.contains("GAME OVER"));
+        assert!(text.contains(&format!("Pieces {}", board.placed)));
+    }
+
+    #[test]
+    fn nothing_locks_while_paused() {
+        let mut board = board_with(PieceKind::O, (3, 0));
+        while board.fall() {}
+        let now = Instant::now();
+        board.update(now);
+
+        board.toggle_pause();
+        assert_eq!(board.status, Status::Paused);
+        board.update(now + LOCK_DELAY * 10);
+        assert!(board.has_active());
+
+        // The lock delay starts over on resuming
+        board.toggle_pause();
+        assert_eq!(board.status, Status::Playing);
+        let resumed = now + LOCK_DELAY * 10;
+        board.update(resumed);
+        assert!(board.has_active());
+        board.update(resumed + LOCK_DELAY);
+        assert!(!board.has_active());
+    }
+
+    fn active_kind(board: &Board) -> PieceKind {
+        board.active.unwrap().piece.kind
+    }
+
+    #[test]
+    fn pieces_come_from_the_preview_queue() {
+        let mut board = Board::new(10, 20).with_seed(3);
+        let next: Vec<PieceKind> = board.pieces.preview(PREVIEW).collect();
+        for kind in next {
+            board.spawn_next();
+            assert_eq!(active_kind(&board), kind);
+            board.hard_drop();
+        }
+    }
+
+    #[test]
+    fn first_hold_brings_in_the_next_piece() {
+        let mut board = Board::new(10, 20).with_seed(5);
+        board.spawn_next();
+        let first = active_kind(&board);
+        let second = board.pieces.preview(1).next().unwrap();
+        board.soft_drop();
+
+        assert!(board.hold());
+        assert_eq!(board.hold, Some(first));
+        assert_eq!(active_kind(&board), second);
+        // The swapped in piece starts again from the top
+        assert_eq!(location(&board), (3, 0));
+    }
+
+    #[test]
+    fn hold_swaps_once_per_piece() {
+        let mut board = Board::new(10, 20).with_seed(9);
+        board.spawn(Piece::new(PieceKind::T));
+        board.hold = Some(PieceKind::I);
+
+        assert!(board.hold());
+        assert_eq!(active_kind(&board), PieceKind::I);
+        assert_eq!(board.hold, Some(PieceKind::T));
+        assert!(!board.hold());
+        assert_eq!(active_kind(&board), PieceKind::I);
+
+        // Free again once the next piece comes in
+        board.hard_drop();
+        board.spawn_next();
+        let kind = active_kind(&board);
+        assert!(board.hold());
+        assert_eq!(active_kind(&board), PieceKind::T);
+        assert_eq!(board.hold, Some(kind));
+    }
+
+    #[test]
+    fn nothing_to_hold_without_an_active_piece() {
+        let mut board = Board::new(10, 20);
+        assert!(!board.hold());
+        assert_eq!(board.hold, None);
+    }
 }
@@ -0,0 +1,307 @@
+/// One of the seven standard tetrominoes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceKind {
+    I,
+    O,
+    T,
+    S,
+    Z,
+    J,
+    L,
+}
+
+impl PieceKind {
+    pub const ALL: [PieceKind; 7] = [
+        PieceKind::I,
+        PieceKind::O,
+        PieceKind::T,
+        PieceKind::S,
+        PieceKind::Z,
+        PieceKind::J,
+        PieceKind::L,
+    ];
+
+    /// Value stored in board cells this kind has locked into, never zero
+    pub fn cell(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    /// The kind that locked into a board cell, if any
+    pub fn from_cell(cell: u8) -> Option<PieceKind> {
+        PieceKind::ALL.get(cell.checked_sub(1)? as usize).copied()
+    }
+
+    // Spawn orientation as (x, y) cells, y down, inside a `box_size` square
+    fn spawn_cells(&self) -> [(usize, usize); 4] {
+        match self {
+            PieceKind::I => [(0, 1), (1, 1), (2, 1), (3, 1)],
+            PieceKind::O => [(1, 0), (2, 0), (1, 1), (2, 1)],
+            PieceKind::T => [(1, 0), (0, 1), (1, 1), (2, 1)],
+            PieceKind::S => [(1, 0), (2, 0), (0, 1), (1, 1)],
+            PieceKind::Z => [(0, 0), (1, 0), (1, 1), (2, 1)],
+            PieceKind::J => [(0, 0), (0, 1), (1, 1), (2, 1)],
+            PieceKind::L => [(2, 0), (0, 1), (1, 1), (2, 1)],
+        }
+    }
+
+    // Side of the square the piece rotates in, as in the Super Rotation System
+    fn box_size(&self) -> usize {
+        match self {
+            PieceKind::I | PieceKind::O => 4,
+            _ => 3,
+        }
+    }
+}
+
+/// Rotation state, named as in the Super Rotation System
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    Spawn,
+    Right,
+    Two,
+    Left,
+}
+
+impl Rotation {
+    pub fn clockwise(&self) -> Rotation {
+        match self {
+            Rotation::Spawn => Rotation::Right,
+            Rotation::Right => Rotation::Two,
+            Rotation::Two => Rotation::Left,
+            Rotation::Left => Rotation::Spawn,
+        }
+    }
+
+    pub fn counter_clockwise(&self) -> Rotation {
+        match self {
+            Rotation::Spawn => Rotation::Left,
+            Rotation::Left => Rotation::Two,
+            Rotation::Two => Rotation::Right,
+            Rotation::Right => Rotation::Spawn,
+        }
+    }
+
+    // Clockwise quarter turns from spawn
+    fn turns(&self) -> usize {
+        match self {
+            Rotation::Spawn => 0,
+            Rotation::Right => 1,
+            Rotation::Two => 2,
+            Rotation::Left => 3,
+        }
+    }
+}
+
+/// A tetromino and the way it is facing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Piece {
+    pub kind: PieceKind,
+    pub rotation: Rotation,
+}
+
+impl Piece {
+    pub fn new(kind: PieceKind) -> Self {
+        Self {
+            kind,
+            rotation: Rotation::Spawn,
+        }
+    }
+
+    /// Filled (x, y) cells of the sprite for the current rotation
+    pub fn cells(&self) -> [(usize, usize); 4] {
+        let size = self.kind.box_size();
+        // The O piece looks the same every way round
+        let turns = match self.kind {
+            PieceKind::O => 0,
+            _ => self.rotation.turns(),
+        };
+
+        self.kind.spawn_cells().map(|(mut x, mut y)| {
+            for _ in 0..turns {
+                (x, y) = (size - 1 - y, x);
+            }
+            (x, y)
+        })
+    }
+}
+
+// Super Rotation System kick offsets, (x right, y up) as usually published,
+// for the clockwise turns 0->R, R->2, 2->L and L->0
+const JLSTZ_KICKS: [[(i32, i32); 5]; 4] = [
+    [(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+    [(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+    [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+    [(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+];
+const I_KICKS: [[(i32, i32); 5]; 4] = [
+    [(0, 0), (-2, 0), (1, 0), (-2, -1), (1, 2)],
+    [(0, 0), (-1, 0), (2, 0), (-1, 2), (2, -1)],
+    [(0, 0), (2, 0), (-1, 0), (2, 1), (-1, -2)],
+    [(0, 0), (1, 0), (-2, 0), (1, -2), (-2, 1)],
+];
+
+/// Offsets (x right, y down) to try in order when `kind` turns `from` one
+/// state `to` a neighbouring one. The O piece never kicks.
+pub fn kicks(kind: PieceKind, from: Rotation, to: Rotation) -> [(i32, i32); 5] {
+    let table = match kind {
+        PieceKind::O => return [(0, 0); 5],
+        PieceKind::I => &I_KICKS,
+        _ => &JLSTZ_KICKS,
+    };
+
+    // A counter-clockwise turn tries the reverse clockwise turn's offsets negated
+    let (row, sign) = match to == from.clockwise() {
+        true => (from.turns(), 1),
+        false => (to.turns(), -1),
+    };
+    table[row].map(|(x, y)| (sign * x, -sign * y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Drawable;
+
+    // Sprite from rows of '#' (filled) and '.' (empty)
+    fn sprite(rows: [&str; 4]) -> [[u8; 4]; 4] {
+        rows.map(|row| {
+            let mut out = [0; 4];
+            for (x, c) in row.chars().enumerate() {
+                out[x] = (c == '#') as u8;
+            }
+            out
+        })
+    }
+
+    fn assert_rotations(kind: PieceKind, expected: [[&str; 4]; 4]) {
+        let mut piece = Piece::new(kind);
+        for rows in expected {
+            assert_eq!(piece.get_sprite(), sprite(rows), "{:?} {:?}", kind, piece.rotation);
+            piece.rotate(true);
+        }
+        assert_eq!(piece.rotation, Rotation::Spawn);
+    }
+
+    #[test]
+    fn i_rotations() {
+        assert_rotations(
+            PieceKind::I,
+            [
+                ["....", "####", "....", "...."],
+                ["..#.", "..#.", "..#.", "..#."],
+                ["....", "....", "####", "...."],
+                [".#..", ".#..", ".#..", ".#.."],
+            ],
+        );
+    }
+
+    #[test]
+    fn o_rotations() {
+        let o = [".##.", ".##.", "....", "...."];
+        assert_rotations(PieceKind::O, [o, o, o, o]);
+    }
+
+    #[test]
+    fn t_rotations() {
+        assert_rotations(
+            PieceKind::T,
+            [
+                [".#..", "###.", "....", "...."],
+                [".#..", ".##.", ".#..", "...."],
+                ["....", "###.", ".#..", "...."],
+                [".#..", "##..", ".#..", "...."],
+            ],
+        );
+    }
+
+    #[test]
+    fn s_rotations() {
+        assert_rotations(
+            PieceKind::S,
+            [
+                [".##.", "##..", "....", "...."],
+                [".#..", ".##.", "..#.", "...."],
+                ["....", ".##.", "##..", "...."],
+                ["#...", "##..", ".#..", "...."],
+            ],
+        );
+    }
+
+    #[test]
+    fn z_rotations() {
+        assert_rotations(
+            PieceKind::Z,
+            [
+                ["##..", ".##.", "....", "...."],
+                ["..#.", ".##.", ".#..", "...."],
+                ["....", "##..", ".##.", "...."],
+                [".#..", "##..", "#...", "...."],
+            ],
+        );
+    }
+
+    #[test]
+    fn j_rotations() {
+        assert_rotations(
+            PieceKind::J,
+            [
+                ["#...", "###.", "....", "...."],
+                [".##.", ".#..", ".#..", "...."],
+                ["....", "###.", "..#.", "...."],
+                [".#..", ".#..", "##..", "...."],
+            ],
+        );
+    }
+
+    #[test]
+    fn l_rotations() {
+        assert_rotations(
+            PieceKind::L,
+            [
+                ["..#.", "###.", "....", "...."],
+                [".#..", ".#..", ".##.", "...."],
+                ["....", "###.", "#...", "...."],
+                ["##..", ".#..", ".#..", "...."],
+            ],
+        );
+    }
+
+    #[test]
+    fn cells_round_trip() {
+        for kind in PieceKind::ALL {
+            assert_ne!(kind.cell(), 0);
+            assert_eq!(PieceKind::from_cell(kind.cell()), Some(kind));
+        }
+        assert_eq!(PieceKind::from_cell(0), None);
+        assert_eq!(PieceKind::from_cell(8), None);
+    }
+
+    #[test]
+    fn counter_clockwise_kicks_mirror_clockwise() {
+        // R->0 from the published JLSTZ table
+        assert_eq!(
+            kicks(PieceKind::T, Rotation::Right, Rotation::Spawn),
+            [(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)]
+        );
+        // 0->L from the published I table
+        assert_eq!(
+            kicks(PieceKind::I, Rotation::Spawn, Rotation::Left),
+            [(0, 0), (-1, 0), (2, 0), (-1, -2), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn counter_clockwise_undoes_clockwise() {
+        for kind in PieceKind::ALL {
+            let mut piece = Piece::new(kind);
+            for _ in 0..4 {
+                let before = piece;
+                piece.rotate(true);
+                piece.rotate(false);
+                assert_eq!(piece, before);
+                piece.rotate(false);
+            }
+        }
+    }
+}
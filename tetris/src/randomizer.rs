@@ -0,0 +1,85 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::piece::PieceKind;
+
+/// Deals pieces from shuffled bags of all seven, so each kind turns up once
+/// every seven pieces and droughts stay short. At least a whole bag is
+/// always waiting, so up to seven pieces can be previewed.
+#[derive(Debug, Clone)]
+pub struct SevenBag {
+    rng: StdRng,
+    upcoming: VecDeque<PieceKind>,
+}
+
+impl SevenBag {
+    pub fn new(seed: u64) -> Self {
+        let mut bag = Self {
+            rng: StdRng::seed_from_u64(seed),
+            upcoming: VecDeque::new(),
+        };
+        bag.fill();
+        bag
+    }
+
+    pub fn next(&mut self) -> PieceKind {
+        let kind = self.upcoming.pop_front().expect("a bag is always waiting");
+        self.fill();
+        kind
+    }
+
+    /// The next `count` pieces (at most seven), without taking them
+    pub fn preview(&self, count: usize) -> impl Iterator<Item = PieceKind> + '_ {
+        self.upcoming.iter().take(count).copied()
+    }
+
+    // Open a new bag once fewer than a bag's worth are waiting
+    fn fill(&mut self) {
+        if self.upcoming.len() < PieceKind::ALL.len() {
+            let mut bag = PieceKind::ALL;
+            bag.shuffle(&mut self.rng);
+            self.upcoming.extend(bag);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_bag_holds_each_piece_once() {
+        let mut bag = SevenBag::new(7);
+        for _ in 0..100 {
+            let mut dealt: Vec<PieceKind> = (0..7).map(|_| bag.next()).collect();
+            dealt.sort_by_key(|kind| PieceKind::ALL.iter().position(|k| k == kind));
+            assert_eq!(dealt, PieceKind::ALL);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_pieces() {
+        let mut a = SevenBag::new(42);
+        let mut b = SevenBag::new(42);
+        let dealt_a: Vec<PieceKind> = (0..50).map(|_| a.next()).collect();
+        let dealt_b: Vec<PieceKind> = (0..50).map(|_| b.next()).collect();
+        assert_eq!(dealt_a, dealt_b);
+
+        let mut c = SevenBag::new(43);
+        let dealt_c: Vec<PieceKind> = (0..50).map(|_| c.next()).collect();
+        assert_ne!(dealt_a, dealt_c);
+    }
+
+    #[test]
+    fn preview_shows_what_comes_next() {
+        let mut bag = SevenBag::new(1);
+        for _ in 0..20 {
+            // Peeks across bag boundaries too
+            let preview: Vec<PieceKind> = bag.preview(7).collect();
+            assert_eq!(preview.len(), 7);
+            let dealt: Vec<PieceKind> = (0..3).map(|_| bag.next()).collect();
+            assert_eq!(dealt, preview[..3]);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::{self, Write};
+
+use crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Color, Print, SetForegroundColor},
+    terminal::{Clear, ClearType},
+};
+
+use crate::piece::PieceKind;
+
+/// Guideline colour for each piece
+pub fn colour(kind: PieceKind) -> Color {
+    match kind {
+        PieceKind::I => Color::Cyan,
+        PieceKind::O => Color::Yellow,
+        PieceKind::T => Color::Magenta,
+        PieceKind::S => Color::Green,
+        PieceKind::Z => Color::Red,
+        PieceKind::J => Color::Blue,
+        PieceKind::L => Color::Rgb { r: 255, g: 165, b: 0 },
+    }
+}
+
+/// One character on screen and its colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Glyph {
+    pub ch: char,
+    pub colour: Color,
+}
+
+impl Default for Glyph {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            colour: Color::Reset,
+        }
+    }
+}
+
+/// A screenful of glyphs, drawn into before it is shown
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    glyphs: Vec<Glyph>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            glyphs: vec![Glyph::default(); width * height],
+        }
+    }
+
+    /// Write `text` from column `x` of row `y`. Anything past the edge is cut off.
+    pub fn print(&mut self, x: usize, y: usize, text: &str, colour: Color) {
+        if y >= self.height {
+            return;
+        }
+        for (x, ch) in (x..self.width).zip(text.chars()) {
+            self.glyphs[y * self.width + x] = Glyph { ch, colour };
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Glyph {
+        self.glyphs[y * self.width + x]
+    }
+}
+
+/// Shows frames on the terminal, only writing the glyphs that changed since
+/// the last one. The first frame clears the screen and is drawn in full.
+#[derive(Debug, Default)]
+pub struct Screen {
+    shown: Option<Frame>,
+}
+
+impl Screen {
+    /// Forget what is on screen so the next frame is drawn in full, e.g.
+    /// after the terminal was cleared or resized
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+
+    pub fn show(&mut self, out: &mut impl Write, frame: Frame) -> io::Result<()> {
+        let shown = self
+            .shown
+            .take()
+            .filter(|shown| (shown.width, shown.height) == (frame.width, frame.height));
+        if shown.is_none() {
+            queue!(out, Clear(ClearType::All))?;
+        }
+
+        // Where the terminal's cursor and colour are, to skip redundant commands
+        let mut cursor = None;
+        let mut colour = None;
+        for y in 0..frame.height {
+            for x in 0..frame.width {
+                let glyph = frame.get(x, y);
+                if shown.as_ref().is_some_and(|shown| shown.get(x, y) == glyph) {
+                    continue;
+                }
+                if cursor != Some((x, y)) {
+                    queue!(out, MoveTo(x as u16, y as u16))?;
+                }
+                if colour != Some(glyph.colour) {
+                    queue!(out, SetForegroundColor(glyph.colour))?;
+                    colour = Some(glyph.colour);
+                }
+                queue!(out, Print(glyph.ch))?;
+                cursor = Some((x + 1, y));
+            }
+        }
+        out.flush()?;
+
+        self.shown = Some(frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(rows: &[&str]) -> Frame {
+        let mut frame = Frame::new(4, rows.len());
+        for (y, row) in rows.iter().enumerate() {
+            frame.print(0, y, row, Color::Reset);
+        }
+        frame
+    }
+
+    fn show(screen: &mut Screen, frame: Frame) -> String {
+        let mut out = Vec::new();
+        screen.show(&mut out, frame).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn text_is_cut_off_at_the_edge() {
+        let mut frame = Frame::new(4, 1);
+        frame.print(2, 0, "abc", Color::Red);
+        frame.print(0, 3, "zzz", Color::Red);
+        assert_eq!(frame.get(1, 0), Glyph::default());
+        assert_eq!(frame.get(3, 0), Glyph { ch: 'b', colour: Color::Red });
+    }
+
+    #[test]
+    fn first_frame_is_drawn_in_full() {
+        let mut screen = Screen::default();
+        let out = show(&mut screen, frame(&["abcd", "efgh"]));
+        assert!(out.contains("abcd"));
+        assert!(out.contains("efgh"));
+    }
+
+    #[test]
+    fn only_changed_glyphs_are_redrawn() {
+        let mut screen = Screen::default();
+        show(&mut screen, frame(&["abcd", "efgh"]));
+
+        assert_eq!(show(&mut screen, frame(&["abcd", "efgh"])), "");
+
+        let out = show(&mut screen, frame(&["abcd", "eXYh"]));
+        let mut expected = Vec::new();
+        queue!(expected, MoveTo(1, 1), SetForegroundColor(Color::Reset), Print("XY")).unwrap();
+        assert_eq!(out.as_bytes(), expected);
+    }
+
+    #[test]
+    fn invalidate_redraws_everything() {
+        let mut screen = Screen::default();
+        show(&mut screen, frame(&["abcd"]));
+        screen.invalidate();
+        assert!(show(&mut screen, frame(&["abcd"])).contains("abcd"));
+    }
+}
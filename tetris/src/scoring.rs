@@ -0,0 +1,273 @@
+use std::{fmt, time::Duration};
+
+const LINES_PER_LEVEL: u32 = 10;
+
+/// How many rows a single lock cleared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineClear {
+    Single,
+    Double,
+    Triple,
+    Tetris,
+}
+
+impl LineClear {
+    pub fn from_count(lines: usize) -> Option<Self> {
+        match lines {
+            1 => Some(LineClear::Single),
+            2 => Some(LineClear::Double),
+            3 => Some(LineClear::Triple),
+            4 => Some(LineClear::Tetris),
+            _ => None,
+        }
+    }
+
+    pub fn lines(&self) -> u32 {
+        match self {
+            LineClear::Single => 1,
+            LineClear::Double => 2,
+            LineClear::Triple => 3,
+            LineClear::Tetris => 4,
+        }
+    }
+}
+
+/// A T piece rotated into a spot it could not have slid into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TSpin {
+    Mini,
+    Full,
+}
+
+/// What one lock scored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Award {
+    pub clear: Option<LineClear>,
+    pub t_spin: Option<TSpin>,
+    pub back_to_back: bool,
+    /// Clears in a row before this one, zero for the first
+    pub combo: u32,
+    pub points: u32,
+}
+
+impl fmt::Display for Award {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.back_to_back {
+            write!(f, "Back-to-back ")?;
+        }
+        match self.t_spin {
+            Some(TSpin::Mini) => write!(f, "Mini T-spin ")?,
+            Some(TSpin::Full) => write!(f, "T-spin ")?,
+            None => {}
+        }
+        if let Some(clear) = self.clear {
+            write!(f, "{:?} ", clear)?;
+        }
+        if self.combo > 0 {
+            write!(f, "{} combo ", self.combo)?;
+        }
+        write!(f, "+{}", self.points)
+    }
+}
+
+/// Guideline scoring. Points for clears and T-spins are multiplied by the
+/// level they happen on.
+#[derive(Debug)]
+pub struct Score {
+    pub points: u32,
+    pub lines: u32,
+    pub start_level: u32,
+    pub last_clear: Option<LineClear>,
+    pub last_award: Option<Award>,
+    // Clears in a row so far, None once a piece locks without clearing
+    combo: Option<u32>,
+    // Whether the last clear was a tetris or a T-spin
+    back_to_back: bool,
+}
+
+impl Default for Score {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl Score {
+    pub fn new(start_level: u32) -> Self {
+        Self {
+            points: 0,
+            lines: 0,
+            start_level,
+            last_clear: None,
+            last_award: None,
+            combo: None,
+            back_to_back: false,
+        }
+    }
+
+    /// Goes up every 10 lines
+    pub fn level(&self) -> u32 {
+        self.start_level + self.lines / LINES_PER_LEVEL
+    }
+
+    /// Time for a piece to fall one row at the current level
+    pub fn gravity(&self) -> Duration {
+        let level = self.level().min(20) as f64 - 1.0;
+        Duration::from_secs_f64((0.8 - level * 0.007).powf(level))
+    }
+
+    pub fn soft_drop(&mut self, rows: u32) {
+        self.points += rows;
+    }
+
+    pub fn hard_drop(&mut self, rows: u32) {
+        self.points += 2 * rows;
+    }
+
+    /// Score a piece locking after clearing `lines` rows
+    pub fn lock(&mut self, lines: usize, t_spin: Option<TSpin>) -> Option<Award> {
+        let clear = LineClear::from_count(lines);
+        let level = self.level();
+
+        let base = match (t_spin, lines) {
+            (None, 0) => 0,
+            (None, 1) => 100,
+            (None, 2) => 300,
+            (None, 3) => 500,
+            (None, _) => 800,
+            (Some(TSpin::Mini), 0) => 100,
+            (Some(TSpin::Mini), 1) => 200,
+            (Some(TSpin::Mini), _) => 400,
+            (Some(TSpin::Full), 0) => 400,
+            (Some(TSpin::Full), 1) => 800,
+            (Some(TSpin::Full), 2) => 1200,
+            (Some(TSpin::Full), _) => 1600,
+        };
+
+        let mut award = Award {
+            clear,
+            t_spin,
+            back_to_back: false,
+            combo: 0,
+            points: base * level,
+        };
+
+        if clear.is_some() {
+            let difficult = lines == 4 || t_spin.is_some();
+            if difficult && self.back_to_back {
+                award.back_to_back = true;
+                award.points = award.points * 3 / 2;
+            }
+            self.back_to_back = difficult;
+
+            let combo = self.combo.map_or(0, |combo| combo + 1);
+            self.combo = Some(combo);
+            award.combo = combo;
+            award.points += 50 * combo * level;
+
+            self.lines += lines as u32;
+            self.last_clear = clear;
+        } else {
+            self.combo = None;
+        }
+
+        self.points += award.points;
+        if award.points == 0 {
+            return None;
+        }
+        self.last_award = Some(award);
+        Some(award)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_clears_scale_with_level() {
+        let mut score = Score::new(1);
+        assert_eq!(score.lock(1, None).unwrap().points, 100);
+        score.lock(0, None);
+        assert_eq!(score.lock(2, None).unwrap().points, 300);
+        score.lock(0, None);
+        assert_eq!(score.lock(3, None).unwrap().points, 500);
+        score.lock(0, None);
+        assert_eq!(score.lock(4, None).unwrap().points, 800);
+        assert_eq!((score.points, score.lines), (1700, 10));
+
+        // Ten lines in, the next clear is worth double
+        assert_eq!(score.level(), 2);
+        score.lock(0, None);
+        assert_eq!(score.lock(1, None).unwrap().points, 200);
+    }
+
+    #[test]
+    fn t_spins() {
+        let mut score = Score::new(1);
+        assert_eq!(score.lock(0, Some(TSpin::Mini)).unwrap().points, 100);
+        assert_eq!(score.lock(0, Some(TSpin::Full)).unwrap().points, 400);
+        assert_eq!(score.lock(1, Some(TSpin::Mini)).unwrap().points, 200);
+        score.lock(0, None);
+        // A plain single breaks back-to-back
+        score.lock(1, None);
+        score.lock(0, None);
+        assert_eq!(score.lock(2, Some(TSpin::Full)).unwrap().points, 1200);
+    }
+
+    #[test]
+    fn back_to_back_difficult_clears() {
+        let mut score = Score::new(1);
+        score.lock(4, None);
+        score.lock(0, None);
+        let award = score.lock(4, None).unwrap();
+        assert!(award.back_to_back);
+        assert_eq!(award.points, 1200);
+
+        // A T-spin with no lines keeps the chain going
+        score.lock(0, None);
+        score.lock(0, Some(TSpin::Full));
+        let award = score.lock(1, Some(TSpin::Full)).unwrap();
+        assert!(award.back_to_back);
+        assert_eq!(award.points, 1200);
+
+        score.lock(0, None);
+        score.lock(2, None);
+        score.lock(0, None);
+        assert!(!score.lock(4, None).unwrap().back_to_back);
+    }
+
+    #[test]
+    fn combos_count_clears_in_a_row() {
+        let mut score = Score::new(1);
+        assert_eq!(score.lock(1, None).unwrap().combo, 0);
+        let award = score.lock(1, None).unwrap();
+        assert_eq!((award.combo, award.points), (1, 150));
+        let award = score.lock(2, None).unwrap();
+        assert_eq!((award.combo, award.points), (2, 400));
+
+        assert_eq!(score.lock(0, None), None);
+        assert_eq!(score.lock(1, None).unwrap().combo, 0);
+    }
+
+    #[test]
+    fn drops_score_per_row() {
+        let mut score = Score::new(5);
+        score.soft_drop(3);
+        score.hard_drop(10);
+        assert_eq!(score.points, 23);
+    }
+
+    #[test]
+    fn gravity_speeds_up_with_level() {
+        let mut score = Score::new(1);
+        assert_eq!(score.gravity(), Duration::from_secs_f64(1.0));
+        let mut last = score.gravity();
+        for _ in 0..14 {
+            score.lines += 10;
+            assert!(score.gravity() < last, "level {}", score.level());
+            last = score.gravity();
+        }
+        // Level 15, about a row per frame at 60 Hz
+        assert!(score.gravity() < Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,64 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+use trajectory::{
+    batch::ProjectileBatch,
+    environment::{Environment, EARTH},
+    Projectile, Vector2,
+};
+
+const DT: f64 = 0.01;
+const WORLD_SIZES: [usize; 3] = [1, 1_000, 100_000];
+
+// A spread of launches so bodies are at different stages of flight
+fn launched(count: usize, environment: &Environment) -> Vec<Projectile> {
+    (0..count)
+        .map(|i| {
+            let mut projectile =
+                Projectile::new(&Vector2::new(1.0, 10.0)).with_environment(environment.clone());
+            let spread = i as f64 / count as f64;
+            projectile.fire(&Vector2::new(5.0 + 10.0 * spread, 20.0 * spread));
+            projectile
+        })
+        .collect()
+}
+
+fn projectile_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("projectile_update");
+    for (name, environment) in [
+        ("vacuum", Environment::default()),
+        ("earth", Environment::for_planet(&EARTH, true)),
+    ] {
+        let mut projectile = launched(1, &environment).remove(0);
+        group.bench_function(name, |b| b.iter(|| projectile.update(black_box(&DT))));
+    }
+    group.finish();
+}
+
+fn world_step(c: &mut Criterion) {
+    let environment = Environment::for_planet(&EARTH, false);
+    let mut group = c.benchmark_group("world_step");
+    group.sample_size(20);
+
+    for count in WORLD_SIZES {
+        let mut projectiles = launched(count, &environment);
+        group.bench_with_input(BenchmarkId::new("projectiles", count), &count, |b, _| {
+            b.iter(|| {
+                for projectile in projectiles.iter_mut() {
+                    projectile.update(black_box(&DT));
+                }
+            })
+        });
+
+        let mut batch = ProjectileBatch::new(environment.clone());
+        for projectile in launched(count, &environment) {
+            batch.push(&projectile);
+        }
+        group.bench_with_input(BenchmarkId::new("batch", count), &count, |b, _| {
+            b.iter(|| batch.step(black_box(DT)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, projectile_update, world_step);
+criterion_main!(benches);
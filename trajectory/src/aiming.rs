@@ -0,0 +1,84 @@
+use crate::Vector2;
+
+/// Metres per second of launch speed for every metre the slingshot is pulled
+pub const SLINGSHOT_GAIN: f64 = 2.0;
+
+/// Maps terminal cells inside a chart's plotting area to world coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartMapping {
+    /// Plotting area in cells: left column, top row, width, height
+    pub left: u16,
+    pub top: u16,
+    pub width: u16,
+    pub height: u16,
+    pub x_bounds: [f64; 2],
+    pub y_bounds: [f64; 2],
+}
+
+impl ChartMapping {
+    pub fn to_world(&self, column: u16, row: u16) -> Vector2 {
+        let fx = column.saturating_sub(self.left) as f64 / self.columns();
+        let fy = row.saturating_sub(self.top) as f64 / self.rows();
+        Vector2::new(
+            self.x_bounds[0] + fx * (self.x_bounds[1] - self.x_bounds[0]),
+            self.y_bounds[1] - fy * (self.y_bounds[1] - self.y_bounds[0]),
+        )
+    }
+
+    pub fn to_cell(&self, point: Vector2) -> (u16, u16) {
+        let fx = (point.x - self.x_bounds[0]) / (self.x_bounds[1] - self.x_bounds[0]);
+        let fy = (self.y_bounds[1] - point.y) / (self.y_bounds[1] - self.y_bounds[0]);
+        (
+            self.left + (fx * self.columns()).round().max(0.0) as u16,
+            self.top + (fy * self.rows()).round().max(0.0) as u16,
+        )
+    }
+
+    // Cells between the first and last column/row, which sit on the bounds
+    fn columns(&self) -> f64 {
+        self.width.saturating_sub(1).max(1) as f64
+    }
+
+    fn rows(&self) -> f64 {
+        self.height.saturating_sub(1).max(1) as f64
+    }
+}
+
+/// Drag-to-aim state: pull back from the launch point, release to fire
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slingshot {
+    pub launch: Vector2,
+    /// Where the drag currently is, in world coordinates
+    pub pull: Vector2,
+    /// Mapping frozen at the start of the drag so the view does not rescale
+    /// under the cursor
+    pub mapping: ChartMapping,
+}
+
+impl Slingshot {
+    /// Start a drag if `column`/`row` is within `grab_cells` of the launch point
+    pub fn grab(launch: Vector2, mapping: ChartMapping, column: u16, row: u16, grab_cells: u16) -> Option<Self> {
+        let (launch_column, launch_row) = mapping.to_cell(launch);
+        let near = launch_column.abs_diff(column) <= grab_cells * 2 && launch_row.abs_diff(row) <= grab_cells;
+        near.then_some(Self {
+            launch,
+            pull: launch,
+            mapping,
+        })
+    }
+
+    pub fn drag(&mut self, column: u16, row: u16) {
+        self.pull = self.mapping.to_world(column, row);
+    }
+
+    /// Launch velocity for the current pull, opposite the drag direction
+    pub fn velocity(&self) -> Vector2 {
+        (self.launch - self.pull) * SLINGSHOT_GAIN
+    }
+
+    /// Speed in m/s and elevation in degrees
+    pub fn speed_and_angle(&self) -> (f64, f64) {
+        let velocity = self.velocity();
+        (velocity.length(), velocity.y.atan2(velocity.x).to_degrees())
+    }
+}
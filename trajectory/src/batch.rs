@@ -0,0 +1,229 @@
+use crate::{
+    environment::Environment, resting, Projectile, Vector2, CONTACT_EFFIENENCY, CONTACT_SKIN,
+};
+
+/// Many projectiles sharing one environment, stored as a struct of arrays so
+/// a step is a tight loop over plain `f64` slices instead of cloning and
+/// updating one `Projectile` at a time.
+///
+/// Stepping matches `Projectile::update`, but bounces are not logged or
+/// recorded as impacts.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectileBatch {
+    pub environment: Environment,
+    pub time: f64,
+    pub px: Vec<f64>,
+    pub py: Vec<f64>,
+    pub vx: Vec<f64>,
+    pub vy: Vec<f64>,
+    pub ax: Vec<f64>,
+    pub ay: Vec<f64>,
+    pub control_x: Vec<f64>,
+    pub control_y: Vec<f64>,
+    pub mass: Vec<f64>,
+    // 0.5 * Cd * A / m, drag acceleration per unit density and speed squared
+    drag: Vec<f64>,
+}
+
+impl ProjectileBatch {
+    pub fn new(environment: Environment) -> Self {
+        Self {
+            environment,
+            ..Default::default()
+        }
+    }
+
+    /// Add a copy of `projectile`'s state. Its own environment is ignored in
+    /// favour of the batch's.
+    pub fn push(&mut self, projectile: &Projectile) {
+        let aero = &projectile.aerodynamics;
+        self.px.push(projectile.position.x);
+        self.py.push(projectile.position.y);
+        self.vx.push(projectile.velocity.x);
+        self.vy.push(projectile.velocity.y);
+        self.ax.push(projectile.acceleration.x);
+        self.ay.push(projectile.acceleration.y);
+        self.control_x.push(projectile.control.x);
+        self.control_y.push(projectile.control.y);
+        self.mass.push(aero.mass);
+        self.drag.push(0.5 * aero.drag_coefficient * aero.area / aero.mass);
+    }
+
+    pub fn len(&self) -> usize {
+        self.px.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.px.is_empty()
+    }
+
+    pub fn position(&self, i: usize) -> Vector2 {
+        Vector2::new(self.px[i], self.py[i])
+    }
+
+    pub fn velocity(&self, i: usize) -> Vector2 {
+        Vector2::new(self.vx[i], self.vy[i])
+    }
+
+    /// Advance every projectile by `dt`
+    pub fn step(&mut self, dt: f64) {
+        if self.environment.obstacles.is_empty() {
+            self.step_open(dt);
+        } else {
+            self.step_with_obstacles(dt);
+        }
+        self.time += dt;
+    }
+
+    // Only the ground to collide with, so contacts are resolved inline
+    fn step_open(&mut self, dt: f64) {
+        let gravity = &self.environment.gravity;
+        let atmosphere = &self.environment.atmosphere;
+
+        for i in 0..self.px.len() {
+            let vx = self.vx[i] + self.ax[i] * dt;
+            let mut vy = self.vy[i] + self.ay[i] * dt;
+            let mut x = self.px[i];
+            let mut y = self.py[i];
+
+            if y < 0.0 || y + vy * dt < 0.0 {
+                // Same contact handling as `Projectile::resolve_contact` against
+                // the ground. Below it and already moving up, only the position
+                // is put back on the surface.
+                let toi = match y > 0.0 {
+                    true => y / -(vy * dt),
+                    false => 0.0,
+                };
+                x += vx * dt * toi;
+                y = CONTACT_SKIN;
+
+                if vy < 0.0 {
+                    let contact_vy = vy - self.ay[i] * dt * (1.0 - toi);
+                    vy = match resting(contact_vy, self.ay[i]) {
+                        true => 0.0,
+                        false => -contact_vy * CONTACT_EFFIENENCY,
+                    };
+                }
+
+                let remaining = dt * (1.0 - toi);
+                x += vx * remaining;
+                y += vy * remaining;
+            } else {
+                x += vx * dt;
+                y += vy * dt;
+            }
+
+            let g = gravity.acceleration(y);
+            let drag = -self.drag[i] * atmosphere.density(y) * (vx * vx + vy * vy).sqrt();
+            self.ax[i] = g.x + drag * vx + self.control_x[i];
+            self.ay[i] = g.y + drag * vy + self.control_y[i];
+
+            self.px[i] = x;
+            self.py[i] = y;
+            self.vx[i] = vx;
+            self.vy[i] = vy;
+        }
+    }
+
+    // Walls need the full swept collision, so reuse one scratch projectile
+    fn step_with_obstacles(&mut self, dt: f64) {
+        let mut scratch = Projectile::default().with_environment(self.environment.clone());
+
+        for i in 0..self.px.len() {
+            scratch.time = self.time;
+            scratch.position = self.position(i);
+            scratch.velocity = self.velocity(i);
+            scratch.acceleration = Vector2::new(self.ax[i], self.ay[i]);
+            scratch.control = Vector2::new(self.control_x[i], self.control_y[i]);
+            // Only Cd * A matters for drag, so fold it all into the coefficient
+            scratch.aerodynamics.mass = self.mass[i];
+            scratch.aerodynamics.area = 1.0;
+            scratch.aerodynamics.drag_coefficient = 2.0 * self.drag[i] * self.mass[i];
+
+            scratch.update(&dt);
+            self.px[i] = scratch.position.x;
+            self.py[i] = scratch.position.y;
+            self.vx[i] = scratch.velocity.x;
+            self.vy[i] = scratch.velocity.y;
+            self.ax[i] = scratch.acceleration.x;
+            self.ay[i] = scratch.acceleration.y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collision::Segment, environment::Atmosphere};
+
+    fn projectiles() -> Vec<Projectile> {
+        [
+            // Free flight, a bounce, sitting on the ground, and below it moving up
+            (Vector2::new(0.0, 10.0), Vector2::new(20.0, 15.0)),
+            (Vector2::new(5.0, 2.0), Vector2::new(3.0, -12.0)),
+            (Vector2::new(1.0, 0.0), Vector2::new(2.0, 0.0)),
+            (Vector2::new(0.0, -0.5), Vector2::new(1.0, 4.0)),
+            (Vector2::new(0.0, -0.5), Vector2::new(1.0, 100.0)),
+        ]
+        .into_iter()
+        .map(|(position, velocity)| {
+            let mut projectile = Projectile::new(&position);
+            projectile.fire(&velocity);
+            projectile
+        })
+        .collect()
+    }
+
+    // Step a batch and the same projectiles one by one, and compare every step
+    fn assert_matches_projectiles(environment: Environment) {
+        let dt = 0.01;
+        let mut singles: Vec<Projectile> = projectiles()
+            .into_iter()
+            .map(|p| p.with_environment(environment.clone()))
+            .collect();
+        let mut batch = ProjectileBatch::new(environment);
+        for projectile in &singles {
+            batch.push(projectile);
+        }
+
+        for step in 0..500 {
+            batch.step(dt);
+            for (i, projectile) in singles.iter_mut().enumerate() {
+                projectile.update(&dt);
+                let position_error = (batch.position(i) - projectile.position).length();
+                let velocity_error = (batch.velocity(i) - projectile.velocity).length();
+                assert!(
+                    position_error < 1e-9 && velocity_error < 1e-9,
+                    "projectile {} differs at step {}: {:?} {:?} against {:?} {:?}",
+                    i,
+                    step,
+                    batch.position(i),
+                    batch.velocity(i),
+                    projectile.position,
+                    projectile.velocity
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_projectile_update_in_vacuum() {
+        assert_matches_projectiles(Environment::default());
+    }
+
+    #[test]
+    fn matches_projectile_update_with_drag() {
+        assert_matches_projectiles(Environment {
+            atmosphere: Atmosphere::Standard,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    fn matches_projectile_update_with_obstacles() {
+        assert_matches_projectiles(Environment {
+            obstacles: vec![Segment::new(Vector2::new(30.0, 0.0), Vector2::new(30.0, 50.0))],
+            ..Default::default()
+        });
+    }
+}
@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+use crate::Vector2;
+
+/// A thin, two-sided wall between `a` and `b`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Segment {
+    pub a: Vector2,
+    pub b: Vector2,
+}
+
+impl Segment {
+    pub fn new(a: Vector2, b: Vector2) -> Self {
+        Self { a, b }
+    }
+}
+
+/// Where along a step something was hit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hit {
+    /// Fraction of the step's movement travelled before contact, 0..=1
+    pub toi: f64,
+    /// Contact point on the surface
+    pub point: Vector2,
+    /// Surface normal facing the incoming projectile
+    pub normal: Vector2,
+}
+
+/// A contact the projectile bounced off
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Impact {
+    /// Simulated time of the contact
+    pub t: f64,
+    pub point: Vector2,
+    pub normal: Vector2,
+    pub energy_lost: f64,
+}
+
+impl Impact {
+    /// Whether this was the ground rather than an obstacle
+    pub fn is_ground(&self) -> bool {
+        self.point.y == 0.0 && self.normal == Vector2::new(0.0, 1.0)
+    }
+}
+
+/// Earliest hit when moving from `start` by `movement` against the ground
+/// (y = 0) and `obstacles`
+pub fn sweep(start: Vector2, movement: Vector2, obstacles: &[Segment]) -> Option<Hit> {
+    let ground = sweep_ground(start, movement);
+    obstacles
+        .iter()
+        .filter_map(|segment| sweep_segment(start, movement, segment))
+        .chain(ground)
+        .min_by(|a, b| a.toi.total_cmp(&b.toi))
+}
+
+fn sweep_ground(start: Vector2, movement: Vector2) -> Option<Hit> {
+    let end_y = start.y + movement.y;
+    let normal = Vector2::new(0.0, 1.0);
+
+    // Already at or below the ground and not leaving it
+    if start.y <= 0.0 && (start.y < 0.0 || end_y < 0.0) {
+        let point = Vector2::new(start.x, 0.0);
+        return Some(Hit { toi: 0.0, point, normal });
+    }
+    if end_y < 0.0 {
+        let toi = start.y / (start.y - end_y);
+        let point = Vector2::new(start.x + movement.x * toi, 0.0);
+        return Some(Hit { toi, point, normal });
+    }
+    None
+}
+
+fn sweep_segment(start: Vector2, movement: Vector2, segment: &Segment) -> Option<Hit> {
+    let edge = segment.b - segment.a;
+    let denom = movement.cross(&edge);
+    if denom.abs() < f64::EPSILON {
+        return None; // parallel
+    }
+
+    let offset = segment.a - start;
+    let toi = offset.cross(&edge) / denom;
+    let along = offset.cross(&movement) / denom;
+    if !(0.0..=1.0).contains(&toi) || !(0.0..=1.0).contains(&along) {
+        return None;
+    }
+
+    let mut normal = edge.perpendicular().normalized();
+    if normal.dot(&movement) > 0.0 {
+        normal = normal * -1.0;
+    }
+    Some(Hit {
+        toi,
+        point: start + movement * toi,
+        normal,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wall(ax: f64, ay: f64, bx: f64, by: f64) -> Segment {
+        Segment::new(Vector2::new(ax, ay), Vector2::new(bx, by))
+    }
+
+    fn close(a: Vector2, b: Vector2) -> bool {
+        (a - b).length() < 1e-12
+    }
+
+    #[test]
+    fn segment_hit_faces_the_projectile() {
+        let walls = [wall(5.0, 0.0, 5.0, 10.0)];
+        let hit = sweep(Vector2::new(0.0, 5.0), Vector2::new(10.0, 0.0), &walls).unwrap();
+        assert_eq!(hit.toi, 0.5);
+        assert!(close(hit.point, Vector2::new(5.0, 5.0)));
+        assert_eq!(hit.normal, Vector2::new(-1.0, 0.0));
+
+        // Walls are two-sided
+        let hit = sweep(Vector2::new(10.0, 5.0), Vector2::new(-10.0, 0.0), &walls).unwrap();
+        assert_eq!(hit.normal, Vector2::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn slanted_segment_normal_is_unit_length() {
+        let walls = [wall(4.0, 0.0, 6.0, 10.0)];
+        let hit = sweep(Vector2::new(0.0, 5.0), Vector2::new(10.0, 0.0), &walls).unwrap();
+        assert!(close(hit.point, Vector2::new(5.0, 5.0)));
+        assert!((hit.normal.length() - 1.0).abs() < 1e-12);
+        assert!(hit.normal.dot(&Vector2::new(10.0, 0.0)) < 0.0);
+    }
+
+    #[test]
+    fn endpoints_count_as_hits() {
+        let walls = [wall(5.0, 0.0, 5.0, 10.0)];
+        let top = sweep(Vector2::new(0.0, 10.0), Vector2::new(10.0, 0.0), &walls).unwrap();
+        assert!(close(top.point, Vector2::new(5.0, 10.0)));
+
+        // Stopping exactly on the wall is a hit at the end of the step
+        let end = sweep(Vector2::new(0.0, 5.0), Vector2::new(5.0, 0.0), &walls).unwrap();
+        assert_eq!(end.toi, 1.0);
+
+        assert_eq!(sweep(Vector2::new(0.0, 10.1), Vector2::new(10.0, 0.0), &walls), None);
+        assert_eq!(sweep(Vector2::new(0.0, 5.0), Vector2::new(4.9, 0.0), &walls), None);
+    }
+
+    #[test]
+    fn grazing_along_a_segment_is_not_a_hit() {
+        let walls = [wall(5.0, 1.0, 5.0, 10.0)];
+        assert_eq!(sweep(Vector2::new(5.0, 2.0), Vector2::new(0.0, 5.0), &walls), None);
+        // Skimming past the bottom end
+        assert_eq!(sweep(Vector2::new(0.0, 0.999), Vector2::new(10.0, 0.0), &walls), None);
+    }
+
+    #[test]
+    fn ground_hits() {
+        let hit = sweep(Vector2::new(0.0, 2.0), Vector2::new(4.0, -4.0), &[]).unwrap();
+        assert_eq!(hit.toi, 0.5);
+        assert_eq!(hit.point, Vector2::new(2.0, 0.0));
+        assert_eq!(hit.normal, Vector2::new(0.0, 1.0));
+
+        // Below ground or on it heading down is a hit straight away
+        let below = sweep(Vector2::new(1.0, -0.5), Vector2::new(1.0, 2.0), &[]).unwrap();
+        assert_eq!((below.toi, below.point), (0.0, Vector2::new(1.0, 0.0)));
+        assert_eq!(sweep(Vector2::new(1.0, 0.0), Vector2::new(1.0, -1.0), &[]).unwrap().toi, 0.0);
+
+        // Leaving the ground or sliding along it is not
+        assert_eq!(sweep(Vector2::new(1.0, 0.0), Vector2::new(1.0, 1.0), &[]), None);
+        assert_eq!(sweep(Vector2::new(1.0, 0.0), Vector2::new(1.0, 0.0), &[]), None);
+    }
+
+    #[test]
+    fn earliest_hit_wins() {
+        let walls = [wall(8.0, 0.0, 8.0, 10.0), wall(3.0, 0.0, 3.0, 10.0)];
+        let hit = sweep(Vector2::new(0.0, 5.0), Vector2::new(10.0, -10.0), &walls).unwrap();
+        assert!(close(hit.point, Vector2::new(3.0, 2.0)));
+
+        let hit = sweep(Vector2::new(0.0, 1.0), Vector2::new(10.0, -10.0), &walls).unwrap();
+        assert_eq!(hit.normal, Vector2::new(0.0, 1.0));
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{collision::Impact, Projectile, Vector2};
+
+/// Snapshot of a projectile's state and energy after one update
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StepSample {
+    pub t: f64,
+    pub position: Vector2,
+    pub velocity: Vector2,
+    pub acceleration: Vector2,
+    pub kinetic: f64,
+    pub potential: f64,
+    pub momentum: Vector2,
+    /// Bounce during this step
+    pub impact: Option<Impact>,
+}
+
+impl StepSample {
+    pub fn capture(t: f64, projectile: &Projectile) -> Self {
+        Self {
+            t,
+            position: projectile.position,
+            velocity: projectile.velocity,
+            acceleration: projectile.acceleration,
+            kinetic: projectile.kinetic_energy(),
+            potential: projectile.potential_energy(),
+            momentum: projectile.momentum(),
+            impact: projectile.last_impact,
+        }
+    }
+
+    pub fn total_energy(&self) -> f64 {
+        self.kinetic + self.potential
+    }
+}
+
+/// (t, value) pairs ready to hand to a chart dataset
+pub fn series(samples: &[StepSample], value: impl Fn(&StepSample) -> f64) -> Vec<(f64, f64)> {
+    samples.iter().map(|s| (s.t, value(s))).collect()
+}
@@ -0,0 +1,289 @@
+use clap::ValueEnum;
+
+use crate::{collision::Segment, Vector2, ACCELERATION_GRAVITY};
+
+const GRAVITATIONAL_CONSTANT: f64 = 6.674_30e-11; // m^3 kg^-1 s^-2
+const STANDARD_GRAVITY: f64 = 9.806_65; // m/s^2, used by the ISA pressure equations
+const AIR_GAS_CONSTANT: f64 = 287.053; // J/(kg K), specific gas constant for dry air
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Planet {
+    pub name: &'static str,
+    pub radius: f64, // m
+    pub mass: f64,   // kg
+    pub atmosphere: Atmosphere,
+}
+
+pub const EARTH: Planet = Planet {
+    name: "Earth",
+    radius: 6.371e6,
+    mass: 5.972e24,
+    atmosphere: Atmosphere::Standard,
+};
+
+pub const MOON: Planet = Planet {
+    name: "Moon",
+    radius: 1.7374e6,
+    mass: 7.342e22,
+    atmosphere: Atmosphere::Vacuum,
+};
+
+pub const MARS: Planet = Planet {
+    name: "Mars",
+    radius: 3.3895e6,
+    mass: 6.4171e23,
+    atmosphere: Atmosphere::Exponential {
+        surface_density: 0.020,
+        scale_height: 11_100.0,
+    },
+};
+
+impl Planet {
+    /// Standard gravitational parameter (GM)
+    pub fn mu(&self) -> f64 {
+        GRAVITATIONAL_CONSTANT * self.mass
+    }
+
+    pub fn surface_gravity(&self) -> f64 {
+        self.mu() / (self.radius * self.radius)
+    }
+
+    /// Magnitude of gravity at `altitude` metres above the surface
+    pub fn gravity_at(&self, altitude: f64) -> f64 {
+        let r = self.radius + altitude.max(0.0);
+        self.mu() / (r * r)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PlanetPreset {
+    Earth,
+    Moon,
+    Mars,
+}
+
+impl PlanetPreset {
+    pub fn planet(&self) -> Planet {
+        match self {
+            PlanetPreset::Earth => EARTH,
+            PlanetPreset::Moon => MOON,
+            PlanetPreset::Mars => MARS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GravityModel {
+    /// Same acceleration at every altitude
+    Constant(Vector2),
+    /// Newtonian gravity falling off with distance from the planet's centre
+    InverseSquare(Planet),
+}
+
+impl Default for GravityModel {
+    fn default() -> Self {
+        GravityModel::Constant(ACCELERATION_GRAVITY)
+    }
+}
+
+impl GravityModel {
+    pub fn acceleration(&self, altitude: f64) -> Vector2 {
+        match self {
+            GravityModel::Constant(g) => *g,
+            GravityModel::InverseSquare(planet) => Vector2::new(0.0, -planet.gravity_at(altitude)),
+        }
+    }
+
+    /// Potential energy per unit mass at `altitude`, zero at the surface
+    pub fn potential(&self, altitude: f64) -> f64 {
+        match self {
+            GravityModel::Constant(g) => -g.y * altitude,
+            GravityModel::InverseSquare(planet) => {
+                planet.mu() * (1.0 / planet.radius - 1.0 / (planet.radius + altitude))
+            }
+        }
+    }
+}
+
+// (base geopotential altitude m, base temperature K, lapse rate K/m, base pressure Pa)
+const ISA_LAYERS: [(f64, f64, f64, f64); 7] = [
+    (0.0, 288.15, -0.0065, 101_325.0),
+    (11_000.0, 216.65, 0.0, 22_632.06),
+    (20_000.0, 216.65, 0.001, 5_474.889),
+    (32_000.0, 228.65, 0.0028, 868.018_7),
+    (47_000.0, 270.65, 0.0, 110.906_3),
+    (51_000.0, 270.65, -0.0028, 66.938_87),
+    (71_000.0, 214.65, -0.002, 3.956_42),
+];
+const ISA_CEILING: f64 = 86_000.0;
+// Earth radius the ISA uses to turn geometric into geopotential altitude
+const ISA_EARTH_RADIUS: f64 = 6_356_766.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Atmosphere {
+    #[default]
+    Vacuum,
+    /// International Standard Atmosphere (1976), valid up to 86 km
+    Standard,
+    /// Isothermal atmosphere, density decays by e every `scale_height` metres
+    Exponential { surface_density: f64, scale_height: f64 },
+}
+
+impl Atmosphere {
+    /// Air density in kg/m^3 at `altitude` metres above the surface
+    pub fn density(&self, altitude: f64) -> f64 {
+        let altitude = altitude.max(0.0);
+        match self {
+            Atmosphere::Vacuum => 0.0,
+            Atmosphere::Standard => isa_density(altitude),
+            Atmosphere::Exponential {
+                surface_density,
+                scale_height,
+            } => surface_density * (-altitude / scale_height).exp(),
+        }
+    }
+}
+
+fn isa_density(altitude: f64) -> f64 {
+    if altitude >= ISA_CEILING {
+        return 0.0;
+    }
+    // The layers are defined in geopotential altitude, which grows slightly
+    // slower than geometric altitude as gravity weakens
+    let altitude = ISA_EARTH_RADIUS * altitude / (ISA_EARTH_RADIUS + altitude);
+
+    let (base, base_temp, lapse, base_pressure) = ISA_LAYERS
+        .iter()
+        .rev()
+        .find(|layer| altitude >= layer.0)
+        .copied()
+        .unwrap_or(ISA_LAYERS[0]);
+
+    let dh = altitude - base;
+    let temp = base_temp + lapse * dh;
+    let pressure = if lapse == 0.0 {
+        base_pressure * (-STANDARD_GRAVITY * dh / (AIR_GAS_CONSTANT * base_temp)).exp()
+    } else {
+        base_pressure * (base_temp / temp).powf(STANDARD_GRAVITY / (AIR_GAS_CONSTANT * lapse))
+    };
+
+    pressure / (AIR_GAS_CONSTANT * temp)
+}
+
+/// Everything about the world a projectile flies through
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Environment {
+    pub gravity: GravityModel,
+    pub atmosphere: Atmosphere,
+    /// Walls the projectile bounces off, on top of the ground at y = 0
+    pub obstacles: Vec<Segment>,
+}
+
+impl Environment {
+    /// Gravity and atmosphere for `planet`. With `inverse_square` false the
+    /// planet's surface gravity is used at every altitude.
+    pub fn for_planet(planet: &Planet, inverse_square: bool) -> Self {
+        let gravity = if inverse_square {
+            GravityModel::InverseSquare(*planet)
+        } else {
+            GravityModel::Constant(Vector2::new(0.0, -planet.surface_gravity()))
+        };
+
+        Self {
+            gravity,
+            atmosphere: planet.atmosphere,
+            obstacles: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(value: f64, expected: f64, relative: f64) -> bool {
+        (value - expected).abs() <= relative * expected.abs()
+    }
+
+    #[test]
+    fn isa_matches_published_densities() {
+        // U.S. Standard Atmosphere 1976, by geometric altitude
+        for (altitude, density) in [
+            (0.0, 1.225),
+            (5_000.0, 0.736_43),
+            (11_000.0, 0.364_80),
+            (20_000.0, 0.088_91),
+            (30_000.0, 0.018_41),
+        ] {
+            let value = Atmosphere::Standard.density(altitude);
+            assert!(close(value, density, 0.002), "{} kg/m^3 at {} m, expected {}", value, altitude, density);
+        }
+    }
+
+    #[test]
+    fn isa_is_continuous_across_layers() {
+        for layer in &ISA_LAYERS[1..] {
+            // Geometric altitude of the layer's base
+            let base = ISA_EARTH_RADIUS * layer.0 / (ISA_EARTH_RADIUS - layer.0);
+            let below = isa_density(base - 1e-3);
+            let above = isa_density(base + 1e-3);
+            assert!(close(below, above, 1e-6), "jump at {} m: {} to {}", base, below, above);
+        }
+    }
+
+    #[test]
+    fn isa_ends_at_the_ceiling_and_clamps_below_ground() {
+        assert_eq!(Atmosphere::Standard.density(ISA_CEILING), 0.0);
+        assert_eq!(Atmosphere::Standard.density(100_000.0), 0.0);
+        assert_eq!(Atmosphere::Standard.density(-50.0), Atmosphere::Standard.density(0.0));
+        assert_eq!(Atmosphere::Vacuum.density(0.0), 0.0);
+    }
+
+    #[test]
+    fn exponential_decays_by_e_per_scale_height() {
+        let mars = MARS.atmosphere;
+        assert_eq!(mars.density(0.0), 0.020);
+        assert!(close(mars.density(11_100.0), 0.020 / std::f64::consts::E, 1e-12));
+    }
+
+    #[test]
+    fn surface_gravity_of_each_planet() {
+        for (preset, gravity) in [
+            (PlanetPreset::Earth, 9.81),
+            (PlanetPreset::Moon, 1.62),
+            (PlanetPreset::Mars, 3.72),
+        ] {
+            let planet = preset.planet();
+            assert!(close(planet.surface_gravity(), gravity, 0.005), "{} {}", planet.name, planet.surface_gravity());
+            assert_eq!(planet.gravity_at(0.0), planet.surface_gravity());
+
+            let constant = Environment::for_planet(&planet, false).gravity;
+            assert_eq!(constant.acceleration(50_000.0), Vector2::new(0.0, -planet.surface_gravity()));
+        }
+    }
+
+    #[test]
+    fn inverse_square_falls_off_with_distance() {
+        let gravity = Environment::for_planet(&EARTH, true).gravity;
+        let surface = EARTH.surface_gravity();
+
+        // Twice as far from the centre, a quarter of the pull
+        assert!(close(-gravity.acceleration(EARTH.radius).y, surface / 4.0, 1e-12));
+        assert!(close(-gravity.acceleration(400_000.0).y, surface * 0.8855, 1e-3));
+        // Below ground is treated as the surface
+        assert_eq!(gravity.acceleration(-10.0), gravity.acceleration(0.0));
+    }
+
+    #[test]
+    fn potential_is_zero_at_the_surface_and_near_gh_close_to_it() {
+        let inverse = GravityModel::InverseSquare(EARTH);
+        let constant = Environment::for_planet(&EARTH, false).gravity;
+        assert_eq!(inverse.potential(0.0), 0.0);
+
+        let h = 100.0;
+        assert!(close(constant.potential(h), EARTH.surface_gravity() * h, 1e-12));
+        assert!(close(inverse.potential(h), constant.potential(h), 1e-4));
+        // Escaping the planet takes GM / R
+        assert!(close(inverse.potential(1e15), EARTH.mu() / EARTH.radius, 1e-6));
+    }
+}
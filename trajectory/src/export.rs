@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+use crate::diagnostics::StepSample;
+use crate::views::{Overlay, View};
+
+const IMAGE_SIZE: (u32, u32) = (1024, 768);
+const PALETTE: [RGBColor; 6] = [RED, BLUE, GREEN, MAGENTA, CYAN, BLACK];
+
+/// Everything needed to draw one chart, independent of the output backend
+pub struct PlotSpec<'a> {
+    pub title: &'a str,
+    pub x_label: &'a str,
+    pub y_label: &'a str,
+    pub x_bounds: [f64; 2],
+    pub y_bounds: [f64; 2],
+    pub series: Vec<(&'a str, &'a [(f64, f64)])>,
+    /// Drawn as unconnected points, e.g. noisy measurements
+    pub scatter: Vec<(&'a str, &'a [(f64, f64)])>,
+}
+
+impl<'a> PlotSpec<'a> {
+    /// The y vs x trajectory, framed the same way as `make_chart`
+    pub fn path(
+        series: Vec<(&'a str, &'a [(f64, f64)])>,
+        scatter: Vec<(&'a str, &'a [(f64, f64)])>,
+    ) -> Self {
+        let all: Vec<(f64, f64)> = series
+            .iter()
+            .chain(scatter.iter())
+            .flat_map(|s| s.1.iter().copied())
+            .collect();
+        let (x_bounds, y_bounds) = crate::views::chart_bounds(&all);
+        Self {
+            title: "Projectile Position",
+            x_label: "X Axis",
+            y_label: "Y Axis",
+            x_bounds,
+            y_bounds,
+            series,
+            scatter,
+        }
+    }
+
+    /// One or more series against time, framed like `make_time_chart`
+    pub fn time(title: &'a str, y_label: &'a str, series: Vec<(&'a str, &'a [(f64, f64)])>) -> Self {
+        let all: Vec<&[(f64, f64)]> = series.iter().map(|s| s.1).collect();
+        let (x_bounds, y_bounds) = crate::views::series_bounds(&all);
+        Self {
+            title,
+            x_label: "t (s)",
+            y_label,
+            x_bounds,
+            y_bounds,
+            series,
+            scatter: vec![],
+        }
+    }
+}
+
+/// Render the same chart the TUI shows for `view` to `path`
+pub fn export_view(
+    path: &Path,
+    view: View,
+    samples: &[StepSample],
+    overlays: &[Overlay],
+) -> Result<(), anyhow::Error> {
+    if view == View::Path {
+        let data: Vec<(f64, f64)> = samples.iter().map(|s| (s.position.x, s.position.y)).collect();
+        let mut series = vec![("Projectile Position", data.as_slice())];
+        let mut scatter = vec![];
+        for overlay in overlays {
+            match overlay.scatter {
+                true => scatter.push((overlay.name, overlay.data)),
+                false => series.push((overlay.name, overlay.data)),
+            }
+        }
+        return export_plot(path, &PlotSpec::path(series, scatter));
+    }
+
+    let view_series = view.series(samples);
+    let series = view_series
+        .iter()
+        .map(|(name, data)| (*name, data.as_slice()))
+        .collect();
+    export_plot(path, &PlotSpec::time(view.title(), view.unit(), series))
+}
+
+/// Render `spec` to `path`. The format is picked from the extension
+/// (`.svg` or `.png`).
+pub fn export_plot(path: &Path, spec: &PlotSpec) -> Result<(), anyhow::Error> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("svg") => draw(SVGBackend::new(path, IMAGE_SIZE).into_drawing_area(), spec),
+        Some("png") => draw(BitMapBackend::new(path, IMAGE_SIZE).into_drawing_area(), spec),
+        _ => bail!("Unsupported export format for {:?}, use .svg or .png", path),
+    }
+}
+
+fn draw<DB: DrawingBackend>(root: DrawingArea<DB, Shift>, spec: &PlotSpec) -> Result<(), anyhow::Error> {
+    let err = |e: DrawingAreaErrorKind<DB::ErrorType>| anyhow!("Failed to draw chart: {:?}", e);
+
+    root.fill(&WHITE).map_err(err)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(spec.title, ("sans-serif", 28))
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(
+            spec.x_bounds[0]..spec.x_bounds[1],
+            spec.y_bounds[0]..spec.y_bounds[1],
+        )
+        .map_err(err)?;
+
+    chart
+        .configure_mesh()
+        .x_desc(spec.x_label)
+        .y_desc(spec.y_label)
+        .draw()
+        .map_err(err)?;
+
+    for (idx, (name, data)) in spec.series.iter().enumerate() {
+        let color = PALETTE[idx % PALETTE.len()];
+        chart
+            .draw_series(LineSeries::new(data.iter().copied(), color.stroke_width(2)))
+            .map_err(err)?
+            .label(*name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+    }
+
+    for (idx, (name, data)) in spec.scatter.iter().enumerate() {
+        let color = PALETTE[(spec.series.len() + idx) % PALETTE.len()];
+        chart
+            .draw_series(data.iter().map(|p| Circle::new(*p, 3, color.filled())))
+            .map_err(err)?
+            .label(*name)
+            .legend(move |(x, y)| Circle::new((x + 10, y), 3, color.filled()));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()
+        .map_err(err)?;
+
+    root.present().map_err(err)?;
+    Ok(())
+}
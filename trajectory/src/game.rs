@@ -0,0 +1,276 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    collision::Segment,
+    environment::Environment,
+    units::{Angle, MetresPerSecond},
+    Projectile, Vector2,
+};
+
+pub const ARENA_WIDTH: f64 = 60.0;
+pub const ARENA_HEIGHT: f64 = 30.0;
+pub const LAUNCH_POSITION: Vector2 = Vector2 { x: 1.0, y: 1.0 };
+const TARGET_RADIUS: f64 = 1.5;
+const MAX_SHOT_TIME: f64 = 15.0;
+const MAX_POWER: f64 = 40.0;
+
+/// One layout of target and walls
+#[derive(Debug, Clone)]
+pub struct Level {
+    /// Centre of the round target, sitting on the ground
+    pub target: Vector2,
+    pub target_radius: f64,
+    pub obstacles: Vec<Segment>,
+}
+
+impl Level {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let target_x = rng.gen_range(25.0..ARENA_WIDTH - 5.0);
+        let wall_count = rng.gen_range(0..=2);
+        let obstacles = (0..wall_count)
+            .map(|_| {
+                let x = rng.gen_range(10.0..target_x - 5.0);
+                let height = rng.gen_range(3.0..15.0);
+                Segment::new(Vector2::new(x, 0.0), Vector2::new(x, height))
+            })
+            .collect();
+
+        Self {
+            target: Vector2::new(target_x, TARGET_RADIUS),
+            target_radius: TARGET_RADIUS,
+            obstacles,
+        }
+    }
+
+    // Whether moving from `from` to `to` passes through the target
+    fn hit_by(&self, from: Vector2, to: Vector2) -> bool {
+        let step = to - from;
+        let length_sq = step.dot(&step);
+        let along = match length_sq > 0.0 {
+            true => ((self.target - from).dot(&step) / length_sq).clamp(0.0, 1.0),
+            false => 0.0,
+        };
+        let closest = from + step * along;
+        (self.target - closest).length() <= self.target_radius
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShotResult {
+    Hit { points: u32 },
+    Miss,
+}
+
+/// Target practice: aim, fire, and hit the target in as few shots as possible
+pub struct Game {
+    /// Elevation in degrees
+    pub angle: f64,
+    /// Launch speed in m/s
+    pub power: f64,
+    pub score: u32,
+    pub attempts: u32,
+    pub hits: u32,
+    pub high_score: u32,
+    pub level: Level,
+    /// Shot in flight, if any
+    pub shot: Option<Projectile>,
+    /// Path of the current or most recent shot
+    pub path: Vec<(f64, f64)>,
+    pub message: String,
+    tries_this_level: u32,
+    rng: StdRng,
+}
+
+impl Game {
+    pub fn new(seed: u64, high_score: u32) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        Self {
+            angle: 45.0,
+            power: 20.0,
+            score: 0,
+            attempts: 0,
+            hits: 0,
+            high_score,
+            level: Level::random(&mut rng),
+            shot: None,
+            path: vec![],
+            message: "Aim with the arrow keys, space to fire".to_string(),
+            tries_this_level: 0,
+            rng,
+        }
+    }
+
+    pub fn adjust_angle(&mut self, delta: f64) {
+        self.angle = (self.angle + delta).clamp(0.0, 90.0);
+    }
+
+    pub fn adjust_power(&mut self, delta: f64) {
+        self.power = (self.power + delta).clamp(1.0, MAX_POWER);
+    }
+
+    pub fn launch_velocity(&self) -> Vector2 {
+        Vector2::from_polar(MetresPerSecond(self.power), Angle::degrees(self.angle))
+    }
+
+    pub fn fire(&mut self) {
+        if self.shot.is_some() {
+            return;
+        }
+
+        let environment = Environment {
+            obstacles: self.level.obstacles.clone(),
+            ..Default::default()
+        };
+        let mut projectile = Projectile::new(&LAUNCH_POSITION).with_environment(environment);
+        projectile.fire(&self.launch_velocity());
+
+        self.attempts += 1;
+        self.tries_this_level += 1;
+        self.path = vec![(LAUNCH_POSITION.x, LAUNCH_POSITION.y)];
+        self.shot = Some(projectile);
+    }
+
+    /// Step the shot in flight. Returns the result once the shot is over.
+    pub fn advance(&mut self, dt: f64, steps: usize) -> Option<ShotResult> {
+        let projectile = self.shot.as_mut()?;
+
+        for _ in 0..steps {
+            let from = projectile.position;
+            let to = projectile.update(&dt);
+            self.path.push((to.x, to.y));
+
+            if self.level.hit_by(from, to) {
+                return Some(self.finish_hit());
+            }
+
+            // The first touch of the ground ends the shot, so it can't bounce
+            // or slide into the target
+            let landed = projectile.on_ground();
+            let outside = to.x < -5.0 || to.x > ARENA_WIDTH + 5.0;
+            if landed || outside || projectile.time > MAX_SHOT_TIME {
+                self.shot = None;
+                self.message = format!("Missed, attempt {} on this target", self.tries_this_level);
+                return Some(ShotResult::Miss);
+            }
+        }
+        None
+    }
+
+    fn finish_hit(&mut self) -> ShotResult {
+        // Fewer tries on a target earn more points
+        let points = 100u32.saturating_sub(25 * (self.tries_this_level - 1)).max(25);
+        self.score += points;
+        self.hits += 1;
+        self.high_score = self.high_score.max(self.score);
+        self.message = format!("Hit! +{} points", points);
+        self.shot = None;
+        self.next_level();
+        ShotResult::Hit { points }
+    }
+
+    /// Move on to a fresh target and walls
+    pub fn next_level(&mut self) {
+        self.level = Level::random(&mut self.rng);
+        self.tries_this_level = 0;
+    }
+}
+
+/// `~/.trajectory_high_score`, or the working directory without a home
+pub fn default_high_score_path() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".trajectory_high_score")
+}
+
+/// Zero when the file is missing or unreadable
+pub fn load_high_score(path: &Path) -> u32 {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+pub fn save_high_score(path: &Path, score: u32) -> io::Result<()> {
+    fs::write(path, format!("{}\n", score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const G: f64 = 9.81;
+
+    // Fly the current shot to the end
+    fn shoot(game: &mut Game, angle: f64, power: f64) -> ShotResult {
+        game.angle = angle;
+        game.power = power;
+        game.fire();
+        loop {
+            if let Some(result) = game.advance(0.01, 10) {
+                return result;
+            }
+        }
+    }
+
+    // Launch speed at `angle` degrees whose vacuum arc passes through the
+    // target's centre
+    fn power_onto_target(level: &Level, angle: f64) -> f64 {
+        let (dx, dy) = (level.target.x - LAUNCH_POSITION.x, level.target.y - LAUNCH_POSITION.y);
+        let angle = angle.to_radians();
+        (G * dx * dx / (2.0 * angle.cos().powi(2) * (angle.tan() * dx - dy))).sqrt()
+    }
+
+    #[test]
+    fn hitting_the_target_scores_and_moves_on() {
+        let mut game = Game::new(1, 0);
+        let level = game.level.clone();
+        let power = power_onto_target(&level, 80.0);
+
+        assert_eq!(shoot(&mut game, 80.0, power), ShotResult::Hit { points: 100 });
+        assert_eq!((game.score, game.hits, game.attempts), (100, 1, 1));
+        assert!(game.shot.is_none());
+        assert_ne!(game.level.target, level.target);
+    }
+
+    #[test]
+    fn later_tries_earn_fewer_points() {
+        let mut game = Game::new(1, 0);
+        let power = power_onto_target(&game.level, 80.0);
+
+        assert_eq!(shoot(&mut game, 80.0, 3.0), ShotResult::Miss);
+        assert_eq!(shoot(&mut game, 80.0, power), ShotResult::Hit { points: 75 });
+        assert_eq!(game.high_score, 75);
+    }
+
+    #[test]
+    fn short_shot_misses_where_it_lands() {
+        let mut game = Game::new(1, 0);
+        let target = game.level.target;
+
+        assert_eq!(shoot(&mut game, 45.0, 10.0), ShotResult::Miss);
+        assert_eq!((game.score, game.attempts), (0, 1));
+        assert!(game.shot.is_none());
+        assert_eq!(game.level.target, target);
+
+        // Ended on the step of the first bounce, around 12.1 m out
+        let (x, y) = *game.path.last().unwrap();
+        assert!(y < 0.05 && (x - 12.1).abs() < 0.2, "ended at ({}, {})", x, y);
+    }
+
+    #[test]
+    fn sliding_along_the_ground_does_not_reach_the_target() {
+        // A flat shot that used to slide all the way into the target
+        let mut game = Game::new(2, 0);
+        assert_eq!(shoot(&mut game, 10.0, 5.0), ShotResult::Miss);
+
+        let (x, _) = *game.path.last().unwrap();
+        assert!(x < 5.0, "ended at x = {}", x);
+        assert!(x < game.level.target.x - game.level.target_radius);
+    }
+}
@@ -0,0 +1,251 @@
+use clap::ValueEnum;
+
+use crate::{
+    diagnostics::StepSample,
+    units::{Metres, MetresPerSecondSquared, Seconds},
+    Projectile, Vector2,
+};
+
+/// A point target flying in a straight line at constant velocity
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Target {
+    pub position: Vector2,
+    pub velocity: Vector2,
+}
+
+impl Target {
+    pub fn update(&mut self, dt: f64) -> Vector2 {
+        self.position = self.position + self.velocity * dt;
+        self.position
+    }
+}
+
+/// Turns the pursuer/target geometry into an acceleration command. Commands
+/// are limited to the lateral axis by `GuidedProjectile`.
+pub trait GuidanceLaw: Send + Sync {
+    fn command(&self, pursuer: &Projectile, target: &Target) -> Vector2;
+}
+
+/// a = N * Vc * d(lambda)/dt, normal to the pursuer's velocity
+#[derive(Debug, Clone, Copy)]
+pub struct ProportionalNavigation {
+    pub gain: f64,
+}
+
+impl GuidanceLaw for ProportionalNavigation {
+    fn command(&self, pursuer: &Projectile, target: &Target) -> Vector2 {
+        let range = target.position - pursuer.position;
+        let relative_velocity = target.velocity - pursuer.velocity;
+        let range_sq = range.dot(&range);
+        if range_sq == 0.0 {
+            return Vector2::default();
+        }
+
+        let los_rate = range.cross(&relative_velocity) / range_sq;
+        let closing_speed = -range.dot(&relative_velocity) / range_sq.sqrt();
+        pursuer.velocity.normalized().perpendicular() * (self.gain * closing_speed * los_rate)
+    }
+}
+
+/// Turn the velocity vector straight at the target, proportional to the
+/// heading error
+#[derive(Debug, Clone, Copy)]
+pub struct PurePursuit {
+    pub gain: f64,
+}
+
+impl GuidanceLaw for PurePursuit {
+    fn command(&self, pursuer: &Projectile, target: &Target) -> Vector2 {
+        let range = target.position - pursuer.position;
+        let velocity = pursuer.velocity;
+        let heading_error = velocity.cross(&range).atan2(velocity.dot(&range));
+        velocity.normalized().perpendicular() * (self.gain * velocity.length() * heading_error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GuidanceKind {
+    /// Proportional navigation
+    Pn,
+    /// Pure pursuit
+    Pursuit,
+}
+
+impl GuidanceKind {
+    pub fn law(&self, gain: f64) -> Box<dyn GuidanceLaw> {
+        match self {
+            GuidanceKind::Pn => Box::new(ProportionalNavigation { gain }),
+            GuidanceKind::Pursuit => Box::new(PurePursuit { gain }),
+        }
+    }
+}
+
+/// A projectile that can steer with up to `max_lateral_acceleration`
+/// sideways to its velocity
+pub struct GuidedProjectile {
+    pub projectile: Projectile,
+    pub max_lateral_acceleration: MetresPerSecondSquared,
+    pub law: Box<dyn GuidanceLaw>,
+}
+
+impl GuidedProjectile {
+    pub fn new(
+        projectile: Projectile,
+        max_lateral_acceleration: MetresPerSecondSquared,
+        law: Box<dyn GuidanceLaw>,
+    ) -> Self {
+        Self {
+            projectile,
+            max_lateral_acceleration,
+            law,
+        }
+    }
+
+    // Returns current position
+    pub fn update(&mut self, dt: f64, target: &Target) -> Vector2 {
+        let command = self.law.command(&self.projectile, target);
+        self.projectile.control = self.limit(command);
+        self.projectile.update(&dt)
+    }
+
+    // Drop any along-track part of the command and clamp what is left
+    fn limit(&self, command: Vector2) -> Vector2 {
+        let normal = self.projectile.velocity.normalized().perpendicular();
+        let max = self.max_lateral_acceleration.0;
+        let lateral = command.dot(&normal).clamp(-max, max);
+        normal * lateral
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Engagement {
+    pub pursuer: Vec<StepSample>,
+    pub target: Vec<(f64, f64)>,
+    /// Closest the pursuer got to the target
+    pub miss_distance: f64,
+    pub time_of_closest_approach: f64,
+}
+
+/// Fly `guided` at `target` for `duration` seconds. Stops early once the
+/// pursuer is inside `hit_radius`.
+pub fn engage(
+    guided: &mut GuidedProjectile,
+    target: &mut Target,
+    duration: Seconds,
+    dt: Seconds,
+    hit_radius: Metres,
+) -> Engagement {
+    let dt = dt.0;
+    let mut engagement = Engagement {
+        miss_distance: (target.position - guided.projectile.position).length(),
+        target: vec![(target.position.x, target.position.y)],
+        ..Default::default()
+    };
+
+    let steps = (duration.0 / dt).round() as usize;
+    for step in 1..=steps {
+        let t = step as f64 * dt;
+        guided.update(dt, target);
+        target.update(dt);
+
+        engagement.pursuer.push(StepSample::capture(t, &guided.projectile));
+        engagement.target.push((target.position.x, target.position.y));
+
+        let distance = (target.position - guided.projectile.position).length();
+        if distance < engagement.miss_distance {
+            engagement.miss_distance = distance;
+            engagement.time_of_closest_approach = t;
+        }
+        if distance <= hit_radius.0 {
+            break;
+        }
+    }
+
+    engagement
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: Seconds = Seconds(0.01);
+
+    fn pursuer() -> Projectile {
+        let mut projectile = Projectile::new(&Vector2::new(0.0, 100.0));
+        projectile.fire(&Vector2::new(60.0, 10.0));
+        projectile
+    }
+
+    // Crossing in front of the pursuer
+    fn crossing() -> Target {
+        Target {
+            position: Vector2::new(400.0, 160.0),
+            velocity: Vector2::new(-15.0, 0.0),
+        }
+    }
+
+    // Flying away from the pursuer, which pure pursuit handles well
+    fn receding() -> Target {
+        Target {
+            position: Vector2::new(200.0, 160.0),
+            velocity: Vector2::new(20.0, 0.0),
+        }
+    }
+
+    fn fly_at(kind: GuidanceKind, gain: f64, mut target: Target, hit_radius: Metres) -> Engagement {
+        let mut guided = GuidedProjectile::new(pursuer(), MetresPerSecondSquared(40.0), kind.law(gain));
+        engage(&mut guided, &mut target, Seconds(15.0), DT, hit_radius)
+    }
+
+    #[test]
+    fn proportional_navigation_intercepts_a_crossing_target() {
+        let engagement = fly_at(GuidanceKind::Pn, 3.0, crossing(), Metres(0.0));
+        assert!(engagement.miss_distance < 0.5, "missed by {} m", engagement.miss_distance);
+
+        let unguided = fly_at(GuidanceKind::Pn, 0.0, crossing(), Metres(0.0));
+        assert!(unguided.miss_distance > 50.0, "missed by {} m", unguided.miss_distance);
+    }
+
+    #[test]
+    fn pure_pursuit_intercepts_a_receding_target() {
+        let engagement = fly_at(GuidanceKind::Pursuit, 2.0, receding(), Metres(0.0));
+        assert!(engagement.miss_distance < 0.5, "missed by {} m", engagement.miss_distance);
+
+        // Chasing the target's current position lags a crossing target
+        let pursuit = fly_at(GuidanceKind::Pursuit, 2.0, crossing(), Metres(0.0));
+        let pn = fly_at(GuidanceKind::Pn, 3.0, crossing(), Metres(0.0));
+        assert!(pn.miss_distance < pursuit.miss_distance);
+    }
+
+    #[test]
+    fn engagement_stops_inside_the_hit_radius() {
+        let engagement = fly_at(GuidanceKind::Pn, 3.0, crossing(), Metres(5.0));
+        let last = engagement.pursuer.last().unwrap();
+
+        assert!(engagement.miss_distance <= 5.0);
+        assert_eq!(last.t, engagement.time_of_closest_approach);
+        assert!(last.t < 15.0);
+        assert_eq!(engagement.target.len(), engagement.pursuer.len() + 1);
+    }
+
+    // Asks for far more than the projectile can pull, and along its velocity too
+    struct Greedy;
+
+    impl GuidanceLaw for Greedy {
+        fn command(&self, _: &Projectile, _: &Target) -> Vector2 {
+            Vector2::new(500.0, 1000.0)
+        }
+    }
+
+    #[test]
+    fn lateral_acceleration_is_clamped() {
+        let mut guided = GuidedProjectile::new(pursuer(), MetresPerSecondSquared(40.0), Box::new(Greedy));
+        guided.update(DT.0, &crossing());
+
+        let control = guided.projectile.control;
+        assert!((control.length() - 40.0).abs() < 1e-9, "{:?}", control);
+        let velocity = pursuer().velocity;
+        assert!(control.dot(&velocity).abs() < 1e-9);
+        assert!(control.cross(&velocity) < 0.0, "turned the wrong way: {:?}", control);
+    }
+}
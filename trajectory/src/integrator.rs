@@ -0,0 +1,289 @@
+use crate::{collision, units::Seconds, Projectile, Vector2, CONTACT_SKIN, RESTING_SPEED};
+
+// Dormand-Prince 5(4) tableau. The dynamics do not depend on time, so the
+// node coefficients (c) are not needed.
+const A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+    [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+    [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+// 5th order weights (same as the last row of A)
+const B: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+// 4th order weights used for the error estimate
+const B_STAR: [f64; 7] = [
+    5179.0 / 57600.0,
+    0.0,
+    7571.0 / 16695.0,
+    393.0 / 640.0,
+    -92097.0 / 339200.0,
+    187.0 / 2100.0,
+    1.0 / 40.0,
+];
+
+// Steps whose time of impact is this close to their end already finish on
+// the surface
+const REFINED_TOI: f64 = 1e-9;
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveSettings {
+    /// Relative error allowed per step
+    pub rtol: f64,
+    /// Absolute error allowed per step (m, m/s)
+    pub atol: f64,
+    pub min_dt: Seconds,
+    pub max_dt: Seconds,
+    pub initial_dt: Seconds,
+    /// Fixed step used while in contact with a surface
+    pub contact_dt: Seconds,
+}
+
+impl Default for AdaptiveSettings {
+    fn default() -> Self {
+        Self {
+            rtol: 1e-6,
+            atol: 1e-6,
+            min_dt: Seconds(1e-6),
+            max_dt: Seconds(0.5),
+            initial_dt: Seconds(0.01),
+            contact_dt: Seconds(1e-3),
+        }
+    }
+}
+
+/// Embedded Runge-Kutta (Dormand-Prince 5(4)) integrator that grows and
+/// shrinks its step to stay inside the tolerances, and shortens steps to
+/// land on ground and obstacle contacts.
+#[derive(Debug, Clone)]
+pub struct AdaptiveIntegrator {
+    pub settings: AdaptiveSettings,
+    /// Step size the next call will try first
+    pub dt: f64,
+    pub accepted: usize,
+    pub rejected: usize,
+}
+
+// [x, y, vx, vy]
+type State = [f64; 4];
+
+impl AdaptiveIntegrator {
+    pub fn new(settings: AdaptiveSettings) -> Self {
+        Self {
+            dt: settings.initial_dt.0,
+            settings,
+            accepted: 0,
+            rejected: 0,
+        }
+    }
+
+    /// Advance `projectile` by one accepted step of at most `max_dt` seconds.
+    /// Returns the step taken.
+    pub fn step(&mut self, projectile: &mut Projectile, max_dt: f64) -> f64 {
+        let (min_dt, max_step, contact_step) = (
+            self.settings.min_dt.0,
+            self.settings.max_dt.0,
+            self.settings.contact_dt.0,
+        );
+        let mut dt = self.dt.min(max_dt).min(max_step);
+
+        loop {
+            let (state, error) = dormand_prince(projectile, dt, &self.settings);
+
+            if error > 1.0 && dt > min_dt {
+                self.rejected += 1;
+                dt = (dt * step_factor(error)).max(min_dt);
+                continue;
+            }
+
+            // Event refinement: if the step crosses a surface, retry a step
+            // that ends at the estimated time of impact
+            let mut end = Vector2::new(state[0], state[1]);
+            let movement = end - projectile.position;
+            let hit = collision::sweep(projectile.position, movement, &projectile.environment.obstacles);
+            if let Some(hit) = hit.filter(|hit| dt * hit.toi > contact_step) {
+                if hit.toi < 1.0 - REFINED_TOI {
+                    dt *= hit.toi;
+                    continue;
+                }
+                // Already refined onto the surface, where retrying would not
+                // shorten the step. Stop just short of it and let the next
+                // step's contact resolve the impact.
+                end = hit.point + hit.normal * CONTACT_SKIN;
+            } else if let Some(hit) = hit {
+                // Close enough, let the fixed step resolve the contact. Its
+                // Euler update reads the cached acceleration, so refresh it.
+                // Resting or sliding contacts keep the full step.
+                let approach_speed = -projectile.velocity.dot(&hit.normal);
+                let contact_dt = match approach_speed < RESTING_SPEED {
+                    true => dt,
+                    false => dt.min(contact_step),
+                };
+                projectile.acceleration = projectile.net_acceleration();
+                projectile.update(&contact_dt);
+                self.accepted += 1;
+                return contact_dt;
+            }
+
+            self.accepted += 1;
+            self.dt = (dt * step_factor(error)).clamp(min_dt, max_step);
+
+            projectile.last_impact = None;
+            projectile.position = end;
+            projectile.velocity = Vector2::new(state[2], state[3]);
+            projectile.acceleration = projectile.net_acceleration();
+            projectile.time += dt;
+            return dt;
+        }
+    }
+}
+
+// Standard step size controller, kept within 0.2x..5x per step
+fn step_factor(error: f64) -> f64 {
+    if error == 0.0 {
+        return 5.0;
+    }
+    (0.9 * error.powf(-0.2)).clamp(0.2, 5.0)
+}
+
+fn derivative(projectile: &Projectile, state: &State) -> State {
+    let position = Vector2::new(state[0], state[1]);
+    let velocity = Vector2::new(state[2], state[3]);
+    let acceleration = projectile.acceleration_at(position, velocity);
+    [velocity.x, velocity.y, acceleration.x, acceleration.y]
+}
+
+// One trial step. Returns the 5th order state and the scaled error norm
+// (<= 1 means within tolerance).
+fn dormand_prince(projectile: &Projectile, dt: f64, settings: &AdaptiveSettings) -> (State, f64) {
+    let y0: State = [
+        projectile.position.x,
+        projectile.position.y,
+        projectile.velocity.x,
+        projectile.velocity.y,
+    ];
+
+    let mut k = [[0.0; 4]; 7];
+    for stage in 0..7 {
+        let mut y = y0;
+        for (prev, a) in A[stage].iter().enumerate().take(stage) {
+            for (value, slope) in y.iter_mut().zip(k[prev]) {
+                *value += dt * a * slope;
+            }
+        }
+        k[stage] = derivative(projectile, &y);
+    }
+
+    let mut y5 = y0;
+    let mut error = 0.0_f64;
+    for i in 0..4 {
+        let high: f64 = (0..7).map(|s| B[s] * k[s][i]).sum();
+        let low: f64 = (0..7).map(|s| B_STAR[s] * k[s][i]).sum();
+        y5[i] = y0[i] + dt * high;
+
+        let scale = settings.atol + settings.rtol * y0[i].abs().max(y5[i].abs());
+        error = error.max((dt * (high - low)).abs() / scale);
+    }
+
+    (y5, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::Segment;
+
+    const G: f64 = 9.81;
+
+    fn launched(position: Vector2, velocity: Vector2) -> Projectile {
+        let mut projectile = Projectile::new(&position);
+        projectile.fire(&velocity);
+        projectile
+    }
+
+    #[test]
+    fn step_grows_in_free_flight() {
+        // High enough not to reach the ground during the test
+        let mut projectile = launched(Vector2::new(0.0, 10_000.0), Vector2::new(30.0, 40.0));
+        let mut integrator = AdaptiveIntegrator::new(AdaptiveSettings::default());
+
+        let first = integrator.step(&mut projectile, f64::INFINITY);
+        for _ in 0..10 {
+            integrator.step(&mut projectile, f64::INFINITY);
+        }
+        assert_eq!(first, 0.01);
+        assert!(integrator.dt > first * 10.0, "dt stayed at {}", integrator.dt);
+        assert_eq!(integrator.rejected, 0);
+    }
+
+    #[test]
+    fn follows_the_vacuum_parabola() {
+        let settings = AdaptiveSettings::default();
+        let (start, velocity) = (Vector2::new(0.0, 5_000.0), Vector2::new(30.0, 40.0));
+        let mut projectile = launched(start, velocity);
+        let mut integrator = AdaptiveIntegrator::new(settings.clone());
+
+        while projectile.time < 20.0 {
+            let remaining = 20.0 - projectile.time;
+            integrator.step(&mut projectile, remaining);
+
+            let t = projectile.time;
+            let expected = Vector2::new(start.x + velocity.x * t, start.y + velocity.y * t - 0.5 * G * t * t);
+            let tolerance = |value: f64| settings.atol + settings.rtol * value.abs();
+            assert!((projectile.position.x - expected.x).abs() < tolerance(expected.x), "at t = {}", t);
+            assert!((projectile.position.y - expected.y).abs() < tolerance(expected.y), "at t = {}", t);
+            assert!((projectile.velocity.y - (velocity.y - G * t)).abs() < tolerance(velocity.y - G * t));
+        }
+        assert!((projectile.time - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn steps_are_refined_onto_the_ground() {
+        let mut projectile = launched(Vector2::new(0.0, 10.0), Vector2::new(1.0, 0.0));
+        let mut integrator = AdaptiveIntegrator::new(AdaptiveSettings::default());
+
+        let mut steps = 0;
+        let impact = loop {
+            let before = projectile.position;
+            let dt = integrator.step(&mut projectile, f64::INFINITY);
+            steps += 1;
+            if let Some(impact) = projectile.last_impact {
+                // The contact step started right above the ground
+                assert!(dt <= 1e-3);
+                assert!(before.y < 0.05, "contact step started at y = {}", before.y);
+                break impact;
+            }
+            assert!(steps < 1000, "never landed");
+        };
+
+        let fall_time = (2.0 * 10.0 / G).sqrt();
+        assert_eq!(impact.point.y, 0.0);
+        assert_eq!(impact.normal, Vector2::new(0.0, 1.0));
+        assert!((impact.t - fall_time).abs() < 1e-3, "landed at t = {}", impact.t);
+        assert!((impact.point.x - fall_time).abs() < 1e-3);
+    }
+
+    #[test]
+    fn steps_are_refined_onto_a_vertical_wall() {
+        let mut projectile = launched(Vector2::new(0.0, 10.0), Vector2::new(10.0, 0.0));
+        projectile.environment.obstacles = vec![Segment::new(Vector2::new(8.0, 0.0), Vector2::new(8.0, 20.0))];
+        let mut integrator = AdaptiveIntegrator::new(AdaptiveSettings::default());
+
+        let mut steps = 0;
+        let impact = loop {
+            integrator.step(&mut projectile, f64::INFINITY);
+            steps += 1;
+            if let Some(impact) = projectile.last_impact {
+                break impact;
+            }
+            assert!(steps < 1000, "never reached the wall");
+        };
+
+        assert_eq!(impact.normal, Vector2::new(-1.0, 0.0));
+        assert!((impact.point.x - 8.0).abs() < 1e-9);
+        assert!((impact.t - 0.8).abs() < 1e-3, "hit at t = {}", impact.t);
+        assert!(projectile.velocity.x < 0.0);
+    }
+}
@@ -0,0 +1,341 @@
+use rand::{rngs::StdRng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::{
+    units::{Metres, Seconds},
+    Projectile, Vector2,
+};
+
+/// Linear Kalman filter for one axis with a constant-acceleration model.
+/// State is [position, velocity, acceleration]; only position is measured.
+#[derive(Debug, Clone)]
+pub struct AxisFilter {
+    pub state: [f64; 3],
+    pub covariance: [[f64; 3]; 3],
+    process_noise: f64,     // jerk spectral density
+    measurement_noise: f64, // position variance
+}
+
+impl AxisFilter {
+    pub fn new(initial_position: f64, process_noise: f64, measurement_noise: f64) -> Self {
+        Self {
+            state: [initial_position, 0.0, 0.0],
+            covariance: [
+                [measurement_noise, 0.0, 0.0],
+                [0.0, 100.0, 0.0],
+                [0.0, 0.0, 100.0],
+            ],
+            process_noise,
+            measurement_noise,
+        }
+    }
+
+    pub fn predict(&mut self, dt: f64) {
+        let f = [
+            [1.0, dt, 0.5 * dt * dt],
+            [0.0, 1.0, dt],
+            [0.0, 0.0, 1.0],
+        ];
+
+        let mut state = [0.0; 3];
+        for (i, row) in f.iter().enumerate() {
+            state[i] = (0..3).map(|j| row[j] * self.state[j]).sum();
+        }
+        self.state = state;
+
+        // P = F P F^T + Q
+        let p = &self.covariance;
+        let mut fp = [[0.0; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                fp[i][j] = (0..3).map(|k| f[i][k] * p[k][j]).sum();
+            }
+        }
+        let q = self.process_noise;
+        let (dt2, dt3, dt4, dt5) = (dt * dt, dt.powi(3), dt.powi(4), dt.powi(5));
+        let noise = [
+            [dt5 / 20.0, dt4 / 8.0, dt3 / 6.0],
+            [dt4 / 8.0, dt3 / 3.0, dt2 / 2.0],
+            [dt3 / 6.0, dt2 / 2.0, dt],
+        ];
+        for i in 0..3 {
+            for j in 0..3 {
+                self.covariance[i][j] =
+                    (0..3).map(|k| fp[i][k] * f[j][k]).sum::<f64>() + q * noise[i][j];
+            }
+        }
+    }
+
+    pub fn update(&mut self, measurement: f64) {
+        // H = [1, 0, 0], so the innovation covariance is a scalar
+        let innovation = measurement - self.state[0];
+        let s = self.covariance[0][0] + self.measurement_noise;
+        let gain = [
+            self.covariance[0][0] / s,
+            self.covariance[1][0] / s,
+            self.covariance[2][0] / s,
+        ];
+
+        for (value, k) in self.state.iter_mut().zip(gain) {
+            *value += k * innovation;
+        }
+
+        // P = (I - K H) P
+        let first_row = self.covariance[0];
+        for (i, k) in gain.iter().enumerate() {
+            for (j, p0j) in first_row.iter().enumerate() {
+                self.covariance[i][j] -= k * p0j;
+            }
+        }
+    }
+}
+
+/// Independent x and y axis filters; the axes do not interact in the model
+#[derive(Debug, Clone)]
+pub struct KalmanFilter2D {
+    pub x: AxisFilter,
+    pub y: AxisFilter,
+}
+
+impl KalmanFilter2D {
+    pub fn new(initial_position: Vector2, process_noise: f64, measurement_noise: f64) -> Self {
+        Self {
+            x: AxisFilter::new(initial_position.x, process_noise, measurement_noise),
+            y: AxisFilter::new(initial_position.y, process_noise, measurement_noise),
+        }
+    }
+
+    pub fn predict(&mut self, dt: f64) {
+        self.x.predict(dt);
+        self.y.predict(dt);
+    }
+
+    pub fn update(&mut self, measurement: Vector2) {
+        self.x.update(measurement.x);
+        self.y.update(measurement.y);
+    }
+
+    pub fn position(&self) -> Vector2 {
+        Vector2::new(self.x.state[0], self.y.state[0])
+    }
+
+    pub fn velocity(&self) -> Vector2 {
+        Vector2::new(self.x.state[1], self.y.state[1])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackingSettings {
+    pub duration: Seconds,
+    pub dt: Seconds,
+    /// Time between position measurements
+    pub measurement_interval: Seconds,
+    /// Standard deviation of the measurement noise
+    pub noise: Metres,
+    pub process_noise: f64,
+    pub seed: u64,
+}
+
+impl Default for TrackingSettings {
+    fn default() -> Self {
+        Self {
+            duration: Seconds(10.0),
+            dt: Seconds(0.01),
+            measurement_interval: Seconds(0.1),
+            noise: Metres(0.5),
+            process_noise: 50.0,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Estimate {
+    pub t: f64,
+    pub position: Vector2,
+    pub velocity: Vector2,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TrackingRun {
+    /// (t, position) of the simulated projectile at every step
+    pub truth: Vec<(f64, Vector2)>,
+    /// (t, noisy position) at every measurement
+    pub measurements: Vec<(f64, Vector2)>,
+    pub estimates: Vec<Estimate>,
+}
+
+impl TrackingRun {
+    /// Root mean square position error of the estimates against the truth
+    pub fn rms_error(&self) -> f64 {
+        let errors: Vec<f64> = self
+            .estimates
+            .iter()
+            .filter_map(|e| {
+                let truth = self.truth.iter().find(|(t, _)| (*t - e.t).abs() < 1e-9)?.1;
+                let dx = truth.x - e.position.x;
+                let dy = truth.y - e.position.y;
+                Some(dx * dx + dy * dy)
+            })
+            .collect();
+        if errors.is_empty() {
+            return 0.0;
+        }
+        (errors.iter().sum::<f64>() / errors.len() as f64).sqrt()
+    }
+}
+
+/// Fly a copy of `projectile`, take noisy position measurements of it and
+/// filter them
+pub fn track(projectile: &Projectile, settings: &TrackingSettings) -> TrackingRun {
+    let mut projectile = projectile.clone();
+    let dt = settings.dt.0;
+    let steps = (settings.duration.0 / dt).round() as usize;
+    let truth: Vec<(f64, Vector2)> = (1..=steps)
+        .map(|step| (step as f64 * dt, projectile.update(&dt)))
+        .collect();
+    track_path(&truth, settings)
+}
+
+/// Take noisy measurements of an already flown `truth` path of (t, position)
+/// samples, one every `measurement_interval`, and filter them. `duration` and
+/// `dt` are not used, the path sets both.
+///
+/// Panics if `settings.noise` is negative or not finite.
+pub fn track_path(truth: &[(f64, Vector2)], settings: &TrackingSettings) -> TrackingRun {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let noise = Normal::new(0.0, settings.noise.0).expect("measurement noise must be finite");
+
+    let mut run = TrackingRun {
+        truth: truth.to_vec(),
+        ..Default::default()
+    };
+    let mut filter: Option<KalmanFilter2D> = None;
+    let mut last_measurement_t = 0.0;
+
+    for &(t, position) in truth {
+        // Small slack so fixed steps that add up to the interval still count
+        if t - last_measurement_t < settings.measurement_interval.0 - 1e-9 {
+            continue;
+        }
+
+        let measurement = Vector2::new(
+            position.x + noise.sample(&mut rng),
+            position.y + noise.sample(&mut rng),
+        );
+        run.measurements.push((t, measurement));
+
+        // The first measurement only seeds the filter
+        if let Some(filter) = filter.as_mut() {
+            filter.predict(t - last_measurement_t);
+            filter.update(measurement);
+        } else {
+            filter = Some(KalmanFilter2D::new(
+                measurement,
+                settings.process_noise,
+                settings.noise.0 * settings.noise.0,
+            ));
+        }
+        last_measurement_t = t;
+
+        if let Some(filter) = &filter {
+            run.estimates.push(Estimate {
+                t,
+                position: filter.position(),
+                velocity: filter.velocity(),
+            });
+        }
+    }
+
+    run
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // High enough that it never reaches the ground in the test's time
+    fn launched() -> Projectile {
+        let mut projectile = Projectile::new(&Vector2::new(0.0, 500.0));
+        projectile.fire(&Vector2::new(12.0, 20.0));
+        projectile
+    }
+
+    // The flight has no jerk at all, so the filter can trust its model
+    fn settings() -> TrackingSettings {
+        TrackingSettings {
+            duration: Seconds(8.0),
+            process_noise: 0.01,
+            seed: 7,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimates_beat_the_raw_measurements() {
+        let run = track(&launched(), &settings());
+        assert_eq!(run.measurements.len(), 80);
+
+        // Once settled, after the first couple of seconds
+        let settled = TrackingRun {
+            estimates: run.estimates.iter().filter(|e| e.t > 2.0).copied().collect(),
+            ..run.clone()
+        };
+        let measured = TrackingRun {
+            estimates: run
+                .measurements
+                .iter()
+                .map(|&(t, position)| Estimate { t, position, velocity: Vector2::default() })
+                .collect(),
+            ..run.clone()
+        };
+        assert!(
+            settled.rms_error() < 0.5 * measured.rms_error(),
+            "rms {} against {} measured",
+            settled.rms_error(),
+            measured.rms_error()
+        );
+    }
+
+    #[test]
+    fn velocity_converges() {
+        let mut projectile = launched();
+        let run = track(&projectile, &settings());
+        for _ in 0..800 {
+            projectile.update(&0.01);
+        }
+
+        let estimate = run.estimates.last().unwrap();
+        assert!((estimate.t - 8.0).abs() < 1e-9);
+        let error = (estimate.velocity - projectile.velocity).length();
+        assert!(error < 0.3, "velocity off by {} m/s", error);
+    }
+
+    #[test]
+    fn same_seed_same_measurements() {
+        let a = track(&launched(), &settings());
+        let b = track(&launched(), &settings());
+        assert_eq!(a.measurements, b.measurements);
+
+        let other = TrackingSettings { seed: 8, ..settings() };
+        assert_ne!(track(&launched(), &other).measurements, a.measurements);
+    }
+
+    #[test]
+    fn measures_uneven_paths_on_the_interval() {
+        // Steps of varying length, as from the adaptive integrator
+        let truth: Vec<(f64, Vector2)> = (1..=200)
+            .scan(0.0, |t, i| {
+                *t += if i % 2 == 0 { 0.01 } else { 0.03 };
+                Some((*t, Vector2::new(*t, 0.0)))
+            })
+            .collect();
+        let run = track_path(&truth, &settings());
+
+        assert_eq!(run.truth, truth);
+        for pair in run.measurements.windows(2) {
+            assert!(pair[1].0 - pair[0].0 >= 0.1 - 1e-9);
+            assert!(pair[1].0 - pair[0].0 < 0.1 + 0.03 + 1e-9);
+        }
+    }
+}
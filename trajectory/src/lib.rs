@@ -0,0 +1,389 @@
+pub mod aiming;
+pub mod batch;
+pub mod collision;
+pub mod diagnostics;
+pub mod environment;
+pub mod export;
+pub mod game;
+pub mod guidance;
+pub mod integrator;
+pub mod kalman;
+pub mod parallel;
+pub mod prediction;
+pub mod summary;
+pub mod units;
+pub mod views;
+
+use collision::Impact;
+use environment::Environment;
+use log::info;
+use serde::Serialize;
+use std::ops::{Add, Mul, Sub};
+use units::{Angle, Kilograms, MetresPerSecond};
+
+// Const definitions
+
+pub const ACCELERATION_GRAVITY: Vector2 = Vector2 { x: 0.0, y: -9.81 };
+pub const CONTACT_EFFIENENCY: f64 = 0.25; // Bounciness
+// Below this normal speed (m/s) at the moment of contact, a contact is treated
+// as resting, not a bounce
+const RESTING_SPEED: f64 = 0.2;
+// Rebounds that would rise less than this (m) off the surface come to rest
+// instead, as they could not be resolved at larger steps anyway
+const RESTING_HEIGHT: f64 = 0.01;
+// Most contacts resolved within a single step
+const MAX_SUBSTEPS: usize = 8;
+// Distance a contact point is pushed off the surface so the next sweep starts clear of it
+const CONTACT_SKIN: f64 = 1e-9;
+
+// Whether a contact at `contact_speed` along the surface normal comes to rest
+// instead of bouncing, with `normal_acceleration` pressing it on (if negative)
+fn resting(contact_speed: f64, normal_acceleration: f64) -> bool {
+    let rebound = -contact_speed * CONTACT_EFFIENENCY;
+    let apex = match normal_acceleration < 0.0 {
+        true => rebound * rebound / (2.0 * -normal_acceleration),
+        false => f64::INFINITY,
+    };
+    -contact_speed < RESTING_SPEED || apex < RESTING_HEIGHT
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Vector2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Vector2 {
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    pub fn length(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    pub fn dot(&self, other: &Vector2) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// z component of the 3D cross product, positive when `other` is
+    /// counter-clockwise from `self`
+    pub fn cross(&self, other: &Vector2) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Velocity of `speed` at `angle` above the +x axis
+    pub fn from_polar(speed: MetresPerSecond, angle: Angle) -> Vector2 {
+        let radians = angle.to_radians();
+        Vector2::new(radians.cos(), radians.sin()) * speed.0
+    }
+
+    /// Direction measured counter-clockwise from the +x axis
+    pub fn angle(&self) -> Angle {
+        Angle::radians(self.y.atan2(self.x))
+    }
+
+    /// Rotated 90 degrees counter-clockwise
+    pub fn perpendicular(&self) -> Vector2 {
+        Vector2::new(-self.y, self.x)
+    }
+
+    /// Unit vector in the same direction, or zero for a zero vector
+    pub fn normalized(&self) -> Vector2 {
+        let length = self.length();
+        if length == 0.0 {
+            return Vector2::default();
+        }
+        *self * (1.0 / length)
+    }
+}
+
+impl Mul<f64> for Vector2 {
+    type Output = Vector2;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl Add<Vector2> for Vector2 {
+    type Output = Vector2;
+
+    fn add(self, rhs: Vector2) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl Sub<Vector2> for Vector2 {
+    type Output = Vector2;
+
+    fn sub(self, rhs: Vector2) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+/// Physical properties used for drag: F = 0.5 * rho * Cd * A * |v| * v
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aerodynamics {
+    pub mass: f64,             // kg
+    pub drag_coefficient: f64, // dimensionless
+    pub area: f64,             // m^2, cross section
+}
+
+impl Default for Aerodynamics {
+    // Roughly a 11cm, 1kg ball
+    fn default() -> Self {
+        Self {
+            mass: 1.0,
+            drag_coefficient: 0.47,
+            area: 0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Projectile {
+    pub position: Vector2,
+    pub velocity: Vector2,
+    pub acceleration: Vector2,
+    pub aerodynamics: Aerodynamics,
+    pub environment: Environment,
+    /// Commanded acceleration from a controller (see `guidance`), added on
+    /// top of gravity and drag
+    pub control: Vector2,
+    /// Simulated seconds since the projectile was created
+    pub time: f64,
+    /// Bounce during the last update, if any
+    pub last_impact: Option<Impact>,
+}
+
+impl Projectile {
+    pub fn new(initial_pos: &Vector2) -> Self {
+        Self {
+            position: *initial_pos,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn fire(&mut self, velocity: &Vector2) {
+        self.velocity = *velocity;
+    }
+
+    /// Fire at `speed` and `angle` of elevation
+    pub fn fire_at(&mut self, speed: MetresPerSecond, angle: Angle) {
+        self.fire(&Vector2::from_polar(speed, angle));
+    }
+
+    /// Whether the last update came down on the ground, either bouncing off
+    /// it or coming to rest on it
+    pub fn on_ground(&self) -> bool {
+        let resting = self.position.y <= CONTACT_SKIN && self.velocity.y == 0.0;
+        resting || self.last_impact.is_some_and(|impact| impact.is_ground())
+    }
+
+    pub fn with_mass(mut self, mass: Kilograms) -> Self {
+        self.aerodynamics.mass = mass.0;
+        self
+    }
+
+    // Returns current position
+    pub fn update(&mut self, dt: &f64) -> Vector2 {
+        self.last_impact = None;
+        self.update_velocity(dt);
+        self.update_position(dt);
+        self.time += dt;
+        self.position
+    }
+
+    fn update_position(&mut self, dt: &f64) {
+        // Sweep the step's movement against the ground and obstacles. On
+        // contact, move up to the time of impact, reflect the normal part of
+        // the velocity (minus some lost energy) and spend the rest of the
+        // step moving away, so bounce points do not depend on dt.
+        let mut remaining = *dt;
+        for _ in 0..MAX_SUBSTEPS {
+            let movement = self.velocity * remaining;
+            let Some(hit) = collision::sweep(self.position, movement, &self.environment.obstacles)
+            else {
+                self.position = self.position + movement;
+                remaining = 0.0;
+                break;
+            };
+
+            self.position = hit.point + hit.normal * CONTACT_SKIN;
+            remaining *= 1.0 - hit.toi;
+            self.resolve_contact(hit.normal, hit.point, *dt - remaining, dt);
+        }
+        if remaining > 0.0 {
+            // Wedged between surfaces, give up on the rest of this step
+            self.position.y = self.position.y.max(0.0);
+        }
+
+        self.acceleration = self.net_acceleration();
+    }
+
+    fn resolve_contact(&mut self, normal: Vector2, point: Vector2, step_time: f64, dt: &f64) {
+        let normal_speed = self.velocity.dot(&normal);
+        if normal_speed >= 0.0 {
+            return; // already moving away
+        }
+        let tangential = self.velocity - normal * normal_speed;
+
+        // The velocity already has the whole step's acceleration added. Wind
+        // it back to the moment of contact, so neither resting nor the bounce
+        // depends on the step size.
+        let contact_speed = normal_speed - self.acceleration.dot(&normal) * (*dt - step_time);
+        if resting(contact_speed, self.acceleration.dot(&normal)) {
+            self.velocity = tangential;
+            return;
+        }
+
+        let energy_before = self.kinetic_energy();
+        self.velocity = tangential + normal * (-contact_speed * CONTACT_EFFIENENCY);
+        let energy_lost = energy_before - self.kinetic_energy();
+        info!(
+            "Bounce at ({:.3}, {:.3}): lost {:.3} J of {:.3} J kinetic",
+            point.x, point.y, energy_lost, energy_before
+        );
+
+        self.last_impact = Some(Impact {
+            t: self.time + step_time,
+            point,
+            normal,
+            energy_lost,
+        });
+    }
+
+    fn net_acceleration(&self) -> Vector2 {
+        self.acceleration_at(self.position, self.velocity)
+    }
+
+    /// Sum of all forces acting on the projectile at the given state, divided
+    /// by its mass
+    pub fn acceleration_at(&self, position: Vector2, velocity: Vector2) -> Vector2 {
+        let altitude = position.y;
+        let gravity = self.environment.gravity.acceleration(altitude);
+
+        let density = self.environment.atmosphere.density(altitude);
+        let aero = &self.aerodynamics;
+        let drag_force =
+            velocity * (-0.5 * density * aero.drag_coefficient * aero.area * velocity.length());
+
+        Vector2::default() + gravity + drag_force * (1.0 / aero.mass) + self.control
+    }
+
+    pub fn kinetic_energy(&self) -> f64 {
+        let speed = self.velocity.length();
+        0.5 * self.aerodynamics.mass * speed * speed
+    }
+
+    pub fn potential_energy(&self) -> f64 {
+        self.aerodynamics.mass * self.environment.gravity.potential(self.position.y)
+    }
+
+    pub fn momentum(&self) -> Vector2 {
+        self.velocity * self.aerodynamics.mass
+    }
+
+    fn update_velocity(&mut self, dt: &f64) {
+        let delta_velocity = self.acceleration * *dt;
+        self.velocity = self.velocity + delta_velocity;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use collision::Segment;
+
+    // Bounces during `duration` seconds at steps of `dt`
+    fn bounces(mut projectile: Projectile, dt: f64, duration: f64) -> Vec<Impact> {
+        let steps = (duration / dt).round() as usize;
+        (0..steps)
+            .filter_map(|_| {
+                projectile.update(&dt);
+                projectile.last_impact
+            })
+            .collect()
+    }
+
+    fn launched(position: Vector2, velocity: Vector2) -> Projectile {
+        let mut projectile = Projectile::new(&position);
+        projectile.fire(&velocity);
+        projectile
+    }
+
+    #[test]
+    fn bounce_count_does_not_depend_on_the_step() {
+        for velocity in [Vector2::new(5.0, 10.0), Vector2::new(3.0, 20.0), Vector2::new(10.0, 0.0)] {
+            let projectile = launched(Vector2::new(1.0, 10.0), velocity);
+            let counts: Vec<usize> = [0.001, 0.01, 0.1, 0.3]
+                .iter()
+                .map(|&dt| bounces(projectile.clone(), dt, 30.0).len())
+                .collect();
+            assert!(counts.iter().all(|&count| count == counts[0]), "{:?} for {:?}", counts, velocity);
+            assert!(counts[0] >= 1);
+        }
+    }
+
+    #[test]
+    fn resting_projectile_stays_put() {
+        for dt in [0.001, 0.1, 0.3] {
+            let mut projectile = launched(Vector2::new(1.0, 0.0), Vector2::default());
+            let impacts = bounces(projectile.clone(), dt, 3.0);
+            for _ in 0..10 {
+                projectile.update(&dt);
+            }
+            assert!(impacts.is_empty(), "bounced {} times at dt {}", impacts.len(), dt);
+            assert!(projectile.position.y >= 0.0 && projectile.position.y < 1e-6);
+            assert!(projectile.on_ground());
+        }
+    }
+
+    #[test]
+    fn bounce_points_do_not_depend_on_the_step() {
+        // A vertical drop onto the ground, and a wall hit in flight
+        let mut projectile = launched(Vector2::new(0.0, 10.0), Vector2::new(2.0, 0.0));
+        projectile.environment.obstacles = vec![Segment::new(Vector2::new(1.0, 0.0), Vector2::new(1.0, 20.0))];
+        let fine = bounces(projectile.clone(), 0.001, 1.5);
+        let coarse = bounces(projectile, 0.05, 1.5);
+
+        assert_eq!(fine.len(), 2);
+        assert_eq!(coarse.len(), 2);
+        for (fine, coarse) in fine.iter().zip(&coarse) {
+            assert_eq!(fine.normal, coarse.normal);
+            assert!((fine.point - coarse.point).length() < 0.2, "{:?} against {:?}", fine, coarse);
+        }
+        assert_eq!(coarse[0].point.x, 1.0);
+        assert_eq!(coarse[1].point.y, 0.0);
+    }
+
+    #[test]
+    fn wedged_projectile_gives_up_after_max_substeps() {
+        // A 1 m box with far more movement in one step than MAX_SUBSTEPS bounces can use
+        let mut projectile = launched(Vector2::new(0.5, 0.5), Vector2::new(1e6, 0.7e6));
+        projectile.environment.obstacles = vec![
+            Segment::new(Vector2::new(0.0, 0.0), Vector2::new(0.0, 1.0)),
+            Segment::new(Vector2::new(1.0, 0.0), Vector2::new(1.0, 1.0)),
+            Segment::new(Vector2::new(0.0, 1.0), Vector2::new(1.0, 1.0)),
+        ];
+
+        let position = projectile.update(&1.0);
+        assert!((0.0..=1.0).contains(&position.x) && (0.0..=1.0).contains(&position.y), "{:?}", position);
+        assert!(projectile.velocity.length().is_finite());
+        assert_eq!(projectile.time, 1.0);
+    }
+}
@@ -1,123 +1,278 @@
-use log::info;
+use clap::{Parser, Subcommand, ValueEnum};
+use log::{debug, info};
 use ratatui::{
-    crossterm::event::{self, KeyCode, KeyEventKind},
+    crossterm::{
+        event::{self, DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEventKind},
+        event::{MouseButton, MouseEventKind},
+        execute,
+    },
     style::Stylize,
-    widgets::Paragraph,
+    widgets::Tabs,
     DefaultTerminal,
 };
 use ratatui::{
-    style::{Style},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
     symbols,
-    widgets::{Axis, Block, Chart, Dataset, GraphType},
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Paragraph},
 };
 use std::{
     io,
-    ops::{Add, Mul, Sub},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use trajectory::{
+    aiming::{ChartMapping, Slingshot},
+    collision::Segment,
+    diagnostics::{self, StepSample},
+    environment::{Atmosphere, Environment, PlanetPreset, EARTH},
+    export,
+    game::{self, Game, ShotResult, ARENA_HEIGHT, ARENA_WIDTH, LAUNCH_POSITION},
+    guidance::{self, GuidanceKind, GuidedProjectile, Target},
+    integrator::{AdaptiveIntegrator, AdaptiveSettings},
+    kalman,
+    prediction::{self, FitOptions},
+    summary::FlightSummary,
+    units::{self, Angle, Kilograms, Metres, MetresPerSecond, MetresPerSecondSquared, Seconds},
+    views::{chart_bounds, series_bounds, Overlay, View},
+    Projectile, Vector2,
 };
-// Const definitions
-
-const ACCELERATION_GRAVITY: Vector2 = Vector2 { x: 0.0, y: -9.81 };
-const CONTACT_EFFIENENCY: f64 = 0.25; // Bounciness
 
-#[derive(Debug, Clone, Default)]
-pub struct Vector2 {
-    pub x: f64,
-    pub y: f64,
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Planet to fire on (gravity and atmosphere presets). Defaults to flat
+    /// Earth gravity without air
+    #[arg(short, long, value_enum)]
+    planet: Option<PlanetPreset>,
+    /// Let gravity fall off with altitude instead of using the surface value
+    #[arg(long)]
+    inverse_square: bool,
+    /// Ignore the planet's atmosphere (no drag)
+    #[arg(long)]
+    vacuum: bool,
+    /// Launch height, e.g. 10m or 30ft (bare numbers are metres)
+    #[arg(long, default_value = "10m")]
+    height: Metres,
+    /// Initial horizontal velocity, e.g. 2m/s or 5mph (bare numbers are m/s)
+    #[arg(long, default_value = "2m/s", allow_hyphen_values = true)]
+    vx: MetresPerSecond,
+    /// Initial vertical velocity, e.g. 2m/s or 5mph (bare numbers are m/s)
+    #[arg(long, default_value = "2m/s", allow_hyphen_values = true)]
+    vy: MetresPerSecond,
+    /// Launch speed, e.g. 30mph. Used with --angle instead of --vx/--vy
+    #[arg(long, requires = "angle", conflicts_with_all = ["vx", "vy"])]
+    speed: Option<MetresPerSecond>,
+    /// Launch elevation with its unit, e.g. 40deg or 0.7rad
+    #[arg(long, requires = "speed", allow_hyphen_values = true)]
+    angle: Option<Angle>,
+    /// Projectile mass, e.g. 1kg or 5lb
+    #[arg(long, value_parser = units::positive::<Kilograms>)]
+    mass: Option<Kilograms>,
+    /// Simulated time, e.g. 10s or 2min (bare numbers are seconds)
+    #[arg(long, default_value = "10s", value_parser = units::non_negative::<Seconds>)]
+    duration: Seconds,
+    /// Thin wall to bounce off, as x1,y1,x2,y2, e.g. 8,0,8,20 or 10ft,0,10ft,3m
+    /// (bare numbers are metres). Repeatable
+    #[arg(long, value_parser = parse_wall)]
+    wall: Vec<Segment>,
+    /// Integration step, e.g. 0.01s or 10ms (bare numbers are seconds)
+    #[arg(long, default_value = "10ms", value_parser = units::positive::<Seconds>)]
+    dt: Seconds,
+    /// Use the adaptive RK45 integrator instead of fixed --dt steps
+    #[arg(long)]
+    adaptive: bool,
+    /// Per-step error tolerance for --adaptive (relative and absolute)
+    #[arg(long, default_value_t = 1e-6)]
+    tolerance: f64,
+    /// Write the chart to an .svg or .png file instead of opening the TUI
+    #[arg(long)]
+    export: Option<PathBuf>,
+    /// Chart to export
+    #[arg(long, value_enum, default_value_t = View::Path)]
+    view: View,
+    /// Print a summary of the flight instead of opening the TUI
+    #[arg(long)]
+    headless: bool,
+    /// Summary format for --headless
+    #[arg(long, value_enum, default_value_t = OutputFormat::Human)]
+    format: OutputFormat,
+    /// Track the projectile with a Kalman filter from noisy position
+    /// measurements and overlay them on the path
+    #[arg(long)]
+    kalman: bool,
+    /// Standard deviation of the measurement noise (bare numbers are metres)
+    #[arg(long, default_value = "0.5m", value_parser = units::non_negative::<Metres>)]
+    noise: Metres,
+    /// Seed for the measurement noise
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Steer the projectile at a moving target with this guidance law. Flown
+    /// with fixed --dt steps, so not with --adaptive
+    #[arg(long, value_enum, conflicts_with = "adaptive")]
+    guidance: Option<GuidanceKind>,
+    /// Navigation constant (pn) or heading gain in 1/s (pursuit)
+    #[arg(long, default_value_t = 3.0)]
+    guidance_gain: f64,
+    /// Largest sideways acceleration the projectile can pull, e.g. 40m/s^2
+    /// or 4g (bare numbers are m/s^2)
+    #[arg(long, default_value = "40m/s^2", value_parser = units::non_negative::<MetresPerSecondSquared>)]
+    max_lateral_accel: MetresPerSecondSquared,
+    /// Target start position x (bare numbers are metres)
+    #[arg(long, default_value = "60m", allow_hyphen_values = true)]
+    target_x: Metres,
+    /// Target start position y (bare numbers are metres)
+    #[arg(long, default_value = "30m", allow_hyphen_values = true)]
+    target_y: Metres,
+    /// Target horizontal velocity (bare numbers are m/s)
+    #[arg(long, default_value = "-8m/s", allow_hyphen_values = true)]
+    target_vx: MetresPerSecond,
+    /// Target vertical velocity (bare numbers are m/s)
+    #[arg(long, default_value = "0m/s", allow_hyphen_values = true)]
+    target_vy: MetresPerSecond,
+    /// Distance counted as a hit, ends the engagement early
+    #[arg(long, default_value = "0.5m")]
+    hit_radius: Metres,
 }
 
-impl Vector2 {
-    pub fn new(x: f64, y: f64) -> Self {
-        Self { x, y }
+fn parse_wall(value: &str) -> Result<Segment, String> {
+    let coords: Vec<f64> = value
+        .split(',')
+        .map(|v| v.parse::<Metres>().map(f64::from))
+        .collect::<Result<_, _>>()?;
+    match coords[..] {
+        [x1, y1, x2, y2] => Ok(Segment::new(Vector2::new(x1, y1), Vector2::new(x2, y2))),
+        _ => Err(format!("expected x1,y1,x2,y2, got {:?}", value)),
     }
 }
 
-impl Mul<f64> for Vector2 {
-    type Output = Vector2;
-
-    fn mul(self, rhs: f64) -> Self::Output {
-        Self {
-            x: self.x * rhs,
-            y: self.y * rhs,
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Human,
+    Json,
 }
 
-impl Add<Vector2> for Vector2 {
-    type Output = Vector2;
-
-    fn add(self, rhs: Vector2) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
+#[derive(Subcommand)]
+enum Command {
+    /// Fit a launch to observed samples and predict where it lands
+    Predict {
+        /// CSV file with t,x,y rows (seconds since launch, metres)
+        csv: PathBuf,
+        /// Also fit the drag coefficient (needs an atmosphere, see --planet)
+        #[arg(long)]
+        fit_drag: bool,
+    },
+    /// Target practice: set angle and power with the arrow keys and hit the target
+    Game {
+        /// Seed for target and wall placement, random if omitted
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Where the high score is kept, defaults to ~/.trajectory_high_score
+        #[arg(long)]
+        high_score_file: Option<PathBuf>,
+    },
 }
 
-impl Sub<Vector2> for Vector2 {
-    type Output = Vector2;
+// Name, colour and points of one line on a chart
+type Series<'a> = (&'a str, Color, &'a [(f64, f64)]);
 
-    fn sub(self, rhs: Vector2) -> Self::Output {
-        Self {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
-    }
-}
-#[derive(Debug, Clone, Default)]
-pub struct Projectile {
-    pub position: Vector2,
-    pub velocity: Vector2,
-    pub acceleration: Vector2,
-}
+// Line chart of one or more named series against time
+fn make_time_chart<'a>(title: &'a str, y_title: &'a str, series: &[Series<'a>]) -> Chart<'a> {
+    let all: Vec<&[(f64, f64)]> = series.iter().map(|s| s.2).collect();
+    let (x_bounds, y_bounds) = series_bounds(&all);
 
-impl Projectile {
-    pub fn new(initial_pos: &Vector2) -> Self {
-        Self {
-            position: initial_pos.clone(),
-            velocity: Vector2::default(),
-            acceleration: Vector2::default(),
-        }
-    }
+    let datasets = series
+        .iter()
+        .map(|(name, color, data)| {
+            Dataset::default()
+                .name(*name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(data)
+        })
+        .collect();
 
-    pub fn fire(&mut self, velocity: &Vector2) {
-        self.velocity = velocity.clone();
-    }
+    let x_axis = Axis::default()
+        .title("t (s)".red())
+        .style(Style::default().white())
+        .bounds(x_bounds)
+        .labels([format!("{:.1}", x_bounds[0]), format!("{:.1}", x_bounds[1])]);
 
-    // Returns current position
-    pub fn update(&mut self, dt: &f64) -> Vector2 {
-        self.update_velocity(dt);
-        self.update_position(dt);
-        self.position.clone()
-    }
+    let y_axis = Axis::default()
+        .title(y_title.red())
+        .style(Style::default().white())
+        .bounds(y_bounds)
+        .labels([format!("{:.2}", y_bounds[0]), format!("{:.2}", y_bounds[1])]);
 
-    fn update_position(&mut self, dt: &f64) {
-        let movement = self.velocity.clone() * (dt.clone());
-        let mut translation = self.position.clone() + movement;
+    Chart::new(datasets)
+        .block(Block::new().title(title))
+        .x_axis(x_axis)
+        .y_axis(y_axis)
+}
 
-        // Ground Check
-        // If the translation (proposed new position) is below or at 0,
-        // Set the y component to 0 (ground) and reverse the current velocity
-        // minus some lost in energy
+const OVERLAY_COLORS: [Color; 3] = [Color::Cyan, Color::Yellow, Color::Green];
 
-        if translation.y <= 0. {
-            translation.y = 0.;
-            let new_velocity = self.velocity.clone() * -1.0 * CONTACT_EFFIENENCY;
-            self.velocity.y = new_velocity.y;
-        }
+// Bounds that fit the path and every overlay
+fn path_bounds(data: &[(f64, f64)], overlays: &[Overlay]) -> ([f64; 2], [f64; 2]) {
+    let all: Vec<(f64, f64)> = overlays
+        .iter()
+        .flat_map(|o| o.data.iter().copied())
+        .chain(data.iter().copied())
+        .collect();
+    chart_bounds(&all)
+}
 
-        self.position = translation;
+fn path_labels(x_bounds: [f64; 2], y_bounds: [f64; 2]) -> ([String; 3], [String; 3]) {
+    (
+        [
+            format!("{:.1}", x_bounds[0]),
+            "0.0".to_string(),
+            format!("{:.1}", x_bounds[1]),
+        ],
+        [
+            format!("{:.1}", y_bounds[0]),
+            "GROUND".to_string(),
+            format!("{:.1}", y_bounds[1]),
+        ],
+    )
+}
 
-        self.acceleration = Vector2::default() + ACCELERATION_GRAVITY;
-    }
+// Plotting area of a `make_chart` chart rendered into `area`, following
+// ratatui's chart layout: the block title takes the top row, the x axis and
+// its labels the bottom two, and the y labels plus axis line the left columns.
+fn plot_mapping(area: Rect, (x_bounds, y_bounds): ([f64; 2], [f64; 2])) -> ChartMapping {
+    let top = area.y + 1;
+    let height = area.height.saturating_sub(1);
+    let (x_labels, y_labels) = path_labels(x_bounds, y_bounds);
+    let label_width = y_labels
+        .iter()
+        .map(|l| l.len())
+        .chain([x_labels[0].len().saturating_sub(1)])
+        .max()
+        .unwrap_or_default()
+        .min(area.width as usize / 3) as u16;
+    let left = area.x + label_width + 1;
 
-    fn update_velocity(&mut self, dt: &f64) {
-        let delta_velocity = self.acceleration.clone() * dt.clone();
-        self.velocity = self.velocity.clone() + delta_velocity;
+    ChartMapping {
+        left,
+        top,
+        width: area.right().saturating_sub(left),
+        height: height.saturating_sub(2),
+        x_bounds,
+        y_bounds,
     }
 }
-fn make_chart<'a>(data: &'a Vec<(f64, f64)>) -> Chart<'a> {
+
+fn make_chart<'a>(
+    data: &'a [(f64, f64)],
+    overlays: &[Overlay<'a>],
+    (x_bounds, y_bounds): ([f64; 2], [f64; 2]),
+) -> Chart<'a> {
     // Create the datasets to fill the chart with
-    let datasets = vec![
+    let mut datasets = vec![
 
         // Line chart
         Dataset::default()
@@ -128,19 +283,36 @@ fn make_chart<'a>(data: &'a Vec<(f64, f64)>) -> Chart<'a> {
             .data(data),
     ];
 
+    for (overlay, color) in overlays.iter().zip(OVERLAY_COLORS.iter().cycle()) {
+        let (graph_type, marker) = match overlay.scatter {
+            true => (GraphType::Scatter, symbols::Marker::Dot),
+            false => (GraphType::Line, symbols::Marker::Braille),
+        };
+        datasets.push(
+            Dataset::default()
+                .name(overlay.name)
+                .marker(marker)
+                .graph_type(graph_type)
+                .style(Style::default().fg(*color))
+                .data(overlay.data),
+        );
+    }
+
+    let (x_labels, y_labels) = path_labels(x_bounds, y_bounds);
+
     // Create the X axis and define its properties
     let x_axis = Axis::default()
         .title("X Axis".red())
         .style(Style::default().white())
-        .bounds([-1.0, 15.0])
-        .labels(["-1.0", "0.0", "15.0"]);
+        .bounds(x_bounds)
+        .labels(x_labels);
 
     // Create the Y axis and define its properties
     let y_axis = Axis::default()
         .title("Y Axis".red())
         .style(Style::default().white())
-        .bounds([-1.0,30.0])
-        .labels(["-1.0", "GROUND", "30.0"]);
+        .bounds(y_bounds)
+        .labels(y_labels);
 
     // Create the chart and link all the parts together
     let chart = Chart::new(datasets)
@@ -148,49 +320,484 @@ fn make_chart<'a>(data: &'a Vec<(f64, f64)>) -> Chart<'a> {
         .x_axis(x_axis)
         .y_axis(y_axis);
 
-    return chart;
+    chart
+}
+const SERIES_COLORS: [Color; 3] = [Color::Red, Color::Cyan, Color::Yellow];
+
+fn run(
+    mut terminal: DefaultTerminal,
+    mut samples: Vec<StepSample>,
+    overlays: &[Overlay],
+    scenery: &[Overlay],
+    launch: Vector2,
+    fly: &dyn Fn(Vector2) -> Vec<StepSample>,
+) -> io::Result<()> {
+    let mut view = View::default();
+    let mut aim: Option<Slingshot> = None;
+    let mut preview: Vec<(f64, f64)> = vec![];
+    // Overlays describe the flight passed in, so hide them once the user fires their own
+    let mut show_overlays = true;
+    let mut path_mapping = None;
+
+    loop {
+        let data: Vec<(f64, f64)> = samples.iter().map(|s| (s.position.x, s.position.y)).collect();
+        let kinetic = diagnostics::series(&samples, |s| s.kinetic);
+        let potential = diagnostics::series(&samples, |s| s.potential);
+        let total = diagnostics::series(&samples, |s| s.total_energy());
+        let momentum_x = diagnostics::series(&samples, |s| s.momentum.x);
+        let momentum_y = diagnostics::series(&samples, |s| s.momentum.y);
+
+        terminal.draw(|frame| {
+            let [header_area, position_area, diagnostics_area] = Layout::vertical([
+                Constraint::Length(1),
+                Constraint::Percentage(60),
+                Constraint::Percentage(40),
+            ])
+            .areas(frame.area());
+            let [tabs_area, status_area] =
+                Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(header_area);
+            let [energy_area, momentum_area] =
+                Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .areas(diagnostics_area);
+
+            let tabs = Tabs::new(View::ALL.iter().map(|v| v.title()))
+                .select(view.index())
+                .highlight_style(Style::default().yellow().bold());
+            frame.render_widget(tabs, tabs_area);
+
+            if view == View::Path {
+                let mut shown: Vec<Overlay> = scenery.to_vec();
+                if show_overlays {
+                    shown.extend_from_slice(overlays);
+                }
+                if aim.is_some() {
+                    shown.push(Overlay { name: "Preview", data: &preview, scatter: false });
+                }
+                let bounds = match &aim {
+                    Some(aim) => (aim.mapping.x_bounds, aim.mapping.y_bounds),
+                    None => path_bounds(&data, &shown),
+                };
+                path_mapping = Some(plot_mapping(position_area, bounds));
+                frame.render_widget(make_chart(&data, &shown, bounds), position_area);
+
+                let status = match &aim {
+                    Some(aim) => {
+                        let (speed, angle) = aim.speed_and_angle();
+                        format!("{:.1} m/s at {:.1} deg, release to fire", speed, angle)
+                    }
+                    None => "Drag from the launch point to aim".to_string(),
+                };
+                frame.render_widget(Paragraph::new(status).right_aligned(), status_area);
+            } else {
+                let view_series = view.series(&samples);
+                let series: Vec<Series> = view_series
+                    .iter()
+                    .zip(SERIES_COLORS)
+                    .map(|((name, data), color)| (*name, color, data.as_slice()))
+                    .collect();
+                let chart = make_time_chart(view.title(), view.unit(), &series);
+                frame.render_widget(chart, position_area);
+            }
+
+            let energy_chart = make_time_chart(
+                "Energy",
+                "J",
+                &[
+                    ("Kinetic", Color::Cyan, &kinetic),
+                    ("Potential", Color::Green, &potential),
+                    ("Total", Color::Yellow, &total),
+                ],
+            );
+            frame.render_widget(energy_chart, energy_area);
+
+            let momentum_chart = make_time_chart(
+                "Momentum",
+                "kg m/s",
+                &[
+                    ("px", Color::Cyan, &momentum_x),
+                    ("py", Color::Magenta, &momentum_y),
+                ],
+            );
+            frame.render_widget(momentum_chart, momentum_area);
+        })?;
+
+        match event::read()? {
+            event::Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Tab | KeyCode::Right => view = view.next(),
+                KeyCode::BackTab | KeyCode::Left => view = view.previous(),
+                KeyCode::Char(c @ '1'..='7') => {
+                    view = View::ALL[c as usize - '1' as usize];
+                }
+                _ => {}
+            },
+            event::Event::Mouse(mouse) if view == View::Path => match mouse.kind {
+                MouseEventKind::Down(MouseButton::Left) => {
+                    aim = path_mapping
+                        .and_then(|m| Slingshot::grab(launch, m, mouse.column, mouse.row, 2));
+                    preview.clear();
+                }
+                MouseEventKind::Drag(MouseButton::Left) => {
+                    if let Some(aim) = aim.as_mut() {
+                        aim.drag(mouse.column, mouse.row);
+                        preview = fly(aim.velocity())
+                            .iter()
+                            .map(|s| (s.position.x, s.position.y))
+                            .collect();
+                    }
+                }
+                MouseEventKind::Up(MouseButton::Left) => {
+                    if let Some(aim) = aim.take() {
+                        info!("Firing at {:?}", aim.velocity());
+                        samples = fly(aim.velocity());
+                        show_overlays = false;
+                        preview.clear();
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
 }
-fn run<'a>(mut terminal: DefaultTerminal, points: &'a Vec<Vector2>) -> io::Result<()> {
+// Time step of a game shot and how many steps to take per frame
+const GAME_DT: f64 = 0.01;
+const GAME_STEPS_PER_FRAME: usize = 3;
+
+fn run_game(mut terminal: DefaultTerminal, game: &mut Game, high_score_file: &Path) -> io::Result<()> {
+    let bounds = ([-1.0, ARENA_WIDTH], [-1.0, ARENA_HEIGHT]);
 
     loop {
+        let walls: Vec<[(f64, f64); 2]> = game
+            .level
+            .obstacles
+            .iter()
+            .map(|w| [(w.a.x, w.a.y), (w.b.x, w.b.y)])
+            .collect();
+        let target: Vec<(f64, f64)> = (0..24)
+            .map(|i| {
+                let a = i as f64 / 24.0 * std::f64::consts::TAU;
+                let r = game.level.target_radius;
+                (game.level.target.x + r * a.cos(), game.level.target.y + r * a.sin())
+            })
+            .collect();
+        // Short line from the launch point showing angle and power
+        let tip = LAUNCH_POSITION + game.launch_velocity() * 0.25;
+        let aim = [(LAUNCH_POSITION.x, LAUNCH_POSITION.y), (tip.x, tip.y)];
+
         terminal.draw(|frame| {
-            let data = points.iter().map(|vec| return (vec.x, vec.y)).collect();
-            let chart = make_chart(&data);
-            frame.render_widget(chart, frame.area());
+            let [status_area, arena_area] =
+                Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(frame.area());
+
+            let mut shown = vec![
+                Overlay { name: "Target", data: &target, scatter: true },
+                Overlay { name: "Aim", data: &aim, scatter: false },
+            ];
+            shown.extend(walls.iter().map(|wall| Overlay { name: "Wall", data: wall, scatter: false }));
+            frame.render_widget(make_chart(&game.path, &shown, bounds), arena_area);
 
+            let status = format!(
+                "Angle {:.0} deg  Power {:.0} m/s  |  Score {}  Hits {}/{}  High score {}\n{}  (arrows aim, space fires, n skips, q quits)",
+                game.angle, game.power, game.score, game.hits, game.attempts, game.high_score, game.message
+            );
+            frame.render_widget(Paragraph::new(status), status_area);
         })?;
-        
 
+        if game.shot.is_some() {
+            if let Some(ShotResult::Hit { points }) = game.advance(GAME_DT, GAME_STEPS_PER_FRAME) {
+                info!("Hit for {} points, score {}", points, game.score);
+                if game.score == game.high_score {
+                    game::save_high_score(high_score_file, game.high_score)?;
+                }
+            }
+        }
+
+        // Keep animating while a shot is in flight, otherwise wait for input
+        if !event::poll(Duration::from_millis(30))? {
+            continue;
+        }
         if let event::Event::Key(key) = event::read()? {
-            if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
-                return Ok(());
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') => return Ok(()),
+                KeyCode::Up => game.adjust_angle(1.0),
+                KeyCode::Down => game.adjust_angle(-1.0),
+                KeyCode::Right => game.adjust_power(1.0),
+                KeyCode::Left => game.adjust_power(-1.0),
+                KeyCode::Char(' ') => game.fire(),
+                KeyCode::Char('n') if game.shot.is_none() => game.next_level(),
+                _ => {}
             }
         }
     }
 }
+
+// Unguided flight of an already fired projectile over the requested duration
+fn fly(projectile: &Projectile, args: &Args) -> Vec<StepSample> {
+    let mut projectile = projectile.clone();
+    let (dt, duration) = (args.dt.0, args.duration.0);
+    let mut samples = vec![];
+    debug!("Staring: {:#?}", projectile);
+
+    if args.adaptive {
+        let mut integrator = AdaptiveIntegrator::new(AdaptiveSettings {
+            rtol: args.tolerance,
+            atol: args.tolerance,
+            initial_dt: args.dt,
+            ..Default::default()
+        });
+        while projectile.time < duration {
+            let remaining = duration - projectile.time;
+            integrator.step(&mut projectile, remaining);
+            samples.push(StepSample::capture(projectile.time, &projectile));
+        }
+        debug!(
+            "Adaptive integration: {} steps accepted, {} rejected",
+            integrator.accepted, integrator.rejected
+        );
+    } else {
+        let mut t = duration;
+        while t > 0.0 {
+            t -= dt;
+            projectile.update(&dt);
+            samples.push(StepSample::capture(duration - t, &projectile));
+        }
+    }
+
+    debug!("Ending: {:#?}", projectile);
+    samples
+}
+
 pub fn main() -> Result<(), anyhow::Error> {
     pretty_env_logger::init();
+    let args = Args::parse();
 
-    let init_pos = Vector2::new(1.0, 10.);
-    let mut projectile = Projectile::new(&init_pos);
+    let mut environment = match (args.planet, args.inverse_square) {
+        (Some(preset), inverse_square) => Environment::for_planet(&preset.planet(), inverse_square),
+        (None, true) => Environment::for_planet(&EARTH, true),
+        (None, false) => Environment::default(),
+    };
+    if args.vacuum {
+        environment.atmosphere = Atmosphere::Vacuum;
+    }
+    environment.obstacles = args.wall.clone();
+
+    if let Some(Command::Game { seed, high_score_file }) = &args.command {
+        let path = high_score_file.clone().unwrap_or_else(game::default_high_score_path);
+        let mut game = Game::new(seed.unwrap_or_else(rand::random), game::load_high_score(&path));
+
+        let terminal = ratatui::init();
+        let app_result = run_game(terminal, &mut game, &path);
+        ratatui::restore();
+
+        println!(
+            "Score {} with {} hits from {} shots, high score {}",
+            game.score, game.hits, game.attempts, game.high_score
+        );
+        return Ok(app_result?);
+    }
+
+    let init_pos = Vector2::new(1.0, args.height.0);
+    let mut projectile = Projectile::new(&init_pos).with_environment(environment);
+    if let Some(mass) = args.mass {
+        projectile = projectile.with_mass(mass);
+    }
+
+    if let Some(Command::Predict { csv, fit_drag }) = &args.command {
+        let observations = prediction::read_observations_csv(csv)?;
+        let options = FitOptions {
+            fit_drag: *fit_drag,
+            ..Default::default()
+        };
+        let result = prediction::predict(&observations, &projectile, &options)?;
+        info!("Prediction: {:#?}", result);
 
-    projectile.fire(&Vector2::new(2.0, 2.0));
+        println!("Fitted {} observations in {} iterations", observations.len(), result.iterations);
+        println!(
+            "Launch position: ({:.3}, {:.3}) m",
+            result.launch_position.x, result.launch_position.y
+        );
+        println!(
+            "Launch velocity: ({:.3}, {:.3}) m/s",
+            result.launch_velocity.x, result.launch_velocity.y
+        );
+        if *fit_drag {
+            println!("Drag coefficient: {:.4}", result.drag_coefficient);
+        }
+        println!("RMS residual: {:.4} m", result.rms_residual);
+        println!(
+            "Landing: x = {:.3} m at t = {:.3} s",
+            result.landing_point.x, result.landing_time
+        );
+        return Ok(());
+    }
 
-    let dt = 0.01;
-    let mut t = 10.0;
-    info!("Staring: {:#?}", projectile);
-    let mut points = vec![];
-    while t > 0.0 {
-        t = t - dt;
-        let result_pos = projectile.update(&dt);
-        points.push(result_pos);
+    match (args.speed, args.angle) {
+        (Some(speed), Some(angle)) => projectile.fire_at(speed, angle),
+        _ => projectile.fire(&Vector2::new(args.vx.0, args.vy.0)),
     }
-    info!("Ending: {:#?}", projectile);
+
+    let tracking_settings = kalman::TrackingSettings {
+        duration: args.duration,
+        dt: args.dt,
+        noise: args.noise,
+        seed: args.seed,
+        ..Default::default()
+    };
+
+    let mut samples = vec![];
+    let mut target_path = vec![];
+    let mut miss_distance = None;
+    if let Some(kind) = args.guidance {
+        let mut guided = GuidedProjectile::new(
+            projectile.clone(),
+            args.max_lateral_accel,
+            kind.law(args.guidance_gain),
+        );
+        let mut target = Target {
+            position: Vector2::new(args.target_x.0, args.target_y.0),
+            velocity: Vector2::new(args.target_vx.0, args.target_vy.0),
+        };
+        let engagement = guidance::engage(&mut guided, &mut target, args.duration, args.dt, args.hit_radius);
+        info!(
+            "Miss distance {:.3} m at t = {:.2} s",
+            engagement.miss_distance, engagement.time_of_closest_approach
+        );
+
+        info!("Ending: {:#?}", guided.projectile);
+        samples = engagement.pursuer;
+        target_path = engagement.target;
+        miss_distance = Some(engagement.miss_distance);
+    } else {
+        samples = fly(&projectile, &args);
+    }
+
+    // Measure the flight that is plotted, guided or not and whichever
+    // integrator flew it
+    let tracking = args.kalman.then(|| {
+        let truth: Vec<(f64, Vector2)> = samples.iter().map(|s| (s.t, s.position)).collect();
+        kalman::track_path(&truth, &tracking_settings)
+    });
+    if let Some(tracking) = &tracking {
+        info!("Kalman RMS position error: {:.3} m", tracking.rms_error());
+        if let Some(last) = tracking.estimates.last() {
+            info!(
+                "Final estimate at t = {:.2}: position {:?}, velocity {:?}",
+                last.t, last.position, last.velocity
+            );
+        }
+    }
+    let measurements: Vec<(f64, f64)> = tracking
+        .iter()
+        .flat_map(|run| run.measurements.iter().map(|(_, p)| (p.x, p.y)))
+        .collect();
+    let estimates: Vec<(f64, f64)> = tracking
+        .iter()
+        .flat_map(|run| run.estimates.iter().map(|e| (e.position.x, e.position.y)))
+        .collect();
+    let truth: Vec<(f64, f64)> = tracking
+        .iter()
+        .flat_map(|run| run.truth.iter().map(|(_, p)| (p.x, p.y)))
+        .collect();
+    let mut overlays = match tracking {
+        Some(_) => vec![
+            Overlay { name: "Measurements", data: &measurements, scatter: true },
+            Overlay { name: "Estimate", data: &estimates, scatter: false },
+            Overlay { name: "Truth", data: &truth, scatter: false },
+        ],
+        None => vec![],
+    };
+    if !target_path.is_empty() {
+        overlays.push(Overlay { name: "Target", data: &target_path, scatter: false });
+    }
+    let walls: Vec<[(f64, f64); 2]> = args
+        .wall
+        .iter()
+        .map(|w| [(w.a.x, w.a.y), (w.b.x, w.b.y)])
+        .collect();
+    let scenery: Vec<Overlay> = walls
+        .iter()
+        .map(|wall| Overlay { name: "Wall", data: wall, scatter: false })
+        .collect();
+
+    if args.headless {
+        let mut summary = FlightSummary::from_samples(StepSample::capture(0.0, &projectile), &samples);
+        summary.miss_distance = miss_distance;
+        match args.format {
+            OutputFormat::Human => println!("{}", summary.to_human()),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+        }
+        return Ok(());
+    }
+
+    if let Some(path) = &args.export {
+        let all: Vec<Overlay> = overlays.iter().chain(&scenery).copied().collect();
+        export::export_view(path, args.view, &samples, &all)?;
+        info!("Exported {} chart to {:?}", args.view.title(), path);
+        return Ok(());
+    }
+
+    // Mouse aiming always flies from the launch point without the extras above
+    projectile.velocity = Vector2::default();
+    let aim_fly = |velocity: Vector2| {
+        let mut projectile = projectile.clone();
+        projectile.fire(&velocity);
+        fly(&projectile, &args)
+    };
 
     let mut terminal = ratatui::init();
     terminal.clear()?;
-    let app_result = run(terminal, &points);
+    execute!(io::stdout(), EnableMouseCapture)?;
+    let app_result = run(terminal, samples, &overlays, &scenery, init_pos, &aim_fly);
+    execute!(io::stdout(), DisableMouseCapture)?;
     ratatui::restore();
 
-    Ok(())
+    Ok(app_result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, clap::Error> {
+        Args::try_parse_from(["trajectory"].iter().chain(args))
+    }
+
+    #[test]
+    fn guidance_is_not_adaptive() {
+        assert!(parse(&["--guidance", "pn"]).is_ok());
+        let error = parse(&["--guidance", "pn", "--adaptive"]).err().map(|e| e.kind());
+        assert_eq!(error, Some(clap::error::ErrorKind::ArgumentConflict));
+    }
+
+    #[test]
+    fn mass_must_be_above_zero() {
+        assert_eq!(parse(&["--mass", "5lb"]).unwrap().mass.map(f64::from), Some(5.0 * 0.453_592_37));
+        assert!(parse(&["--mass", "0kg"]).is_err());
+        assert!(parse(&["--mass=-1kg"]).is_err());
+    }
+
+    #[test]
+    fn lateral_acceleration_takes_units() {
+        assert_eq!(parse(&[]).unwrap().max_lateral_accel, MetresPerSecondSquared(40.0));
+        let accel = parse(&["--max-lateral-accel", "2g"]).unwrap().max_lateral_accel;
+        assert!((accel.0 - 19.6133).abs() < 1e-9);
+        assert!(parse(&["--max-lateral-accel=-1"]).is_err());
+        assert!(parse(&["--max-lateral-accel", "3m/s"]).is_err());
+    }
+
+    #[test]
+    fn wall_coordinates_take_units() {
+        assert_eq!(
+            parse_wall("8,0,8,20"),
+            Ok(Segment::new(Vector2::new(8.0, 0.0), Vector2::new(8.0, 20.0)))
+        );
+        let wall = parse_wall("10ft, 0, 300cm, 2m").unwrap();
+        assert!((wall.a.x - 3.048).abs() < 1e-9);
+        assert_eq!(wall.b, Vector2::new(3.0, 2.0));
+        assert!(parse_wall("8,0,8").is_err());
+        assert!(parse_wall("8,0,8,20s").is_err());
+    }
 }
@@ -0,0 +1,89 @@
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::Projectile;
+
+/// Seed for run `index` of a batch started with `seed`. Mixed with
+/// SplitMix64 so neighbouring runs get unrelated random streams.
+pub fn run_seed(seed: u64, index: usize) -> u64 {
+    let mut z = seed.wrapping_add((index as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Call `run` for every index in `0..runs` across the rayon thread pool.
+/// Each run gets its own generator seeded from `seed` and its index, and
+/// results come back in index order, so the output does not depend on the
+/// number of threads.
+pub fn simulate<R, F>(runs: usize, seed: u64, run: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize, &mut StdRng) -> R + Sync,
+{
+    (0..runs)
+        .into_par_iter()
+        .map(|index| {
+            let mut rng = StdRng::seed_from_u64(run_seed(seed, index));
+            run(index, &mut rng)
+        })
+        .collect()
+}
+
+/// Fly every projectile for `duration` seconds with fixed steps of `dt`,
+/// in parallel. Returns the projectiles at the end of their flights.
+pub fn fly_all(projectiles: &[Projectile], dt: f64, duration: f64) -> Vec<Projectile> {
+    projectiles
+        .par_iter()
+        .map(|projectile| {
+            let mut projectile = projectile.clone();
+            while projectile.time < duration {
+                projectile.update(&dt);
+            }
+            projectile
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::Vector2;
+
+    // A small Monte Carlo batch: random launches flown to the end
+    fn monte_carlo() -> Vec<(u64, Vector2)> {
+        simulate(64, 42, |index, rng| {
+            let mut projectile = Projectile::new(&Vector2::new(0.0, 1.0));
+            projectile.fire(&Vector2::new(rng.gen_range(5.0..15.0), rng.gen_range(5.0..15.0)));
+            for _ in 0..300 {
+                projectile.update(&0.01);
+            }
+            (run_seed(42, index), projectile.position)
+        })
+    }
+
+    fn in_pool<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap()
+            .install(f)
+    }
+
+    #[test]
+    fn output_does_not_depend_on_thread_count() {
+        let single = in_pool(1, monte_carlo);
+        let many = in_pool(4, monte_carlo);
+        assert_eq!(single.len(), 64);
+        assert_eq!(single, many);
+    }
+
+    #[test]
+    fn runs_get_different_streams() {
+        let results = monte_carlo();
+        assert_ne!(results[0].1, results[1].1);
+        assert_ne!(run_seed(42, 0), run_seed(43, 0));
+    }
+}
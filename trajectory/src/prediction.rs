@@ -0,0 +1,423 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::{environment::Atmosphere, units::Seconds, Projectile, Vector2};
+
+/// A measured position at `t` seconds after launch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Observation {
+    pub t: f64,
+    pub position: Vector2,
+}
+
+#[derive(Debug, Clone)]
+pub struct FitOptions {
+    /// Also fit the drag coefficient (needs an atmosphere)
+    pub fit_drag: bool,
+    /// Integration step used when simulating candidate launches
+    pub dt: Seconds,
+    pub max_iterations: usize,
+    /// Longest flight searched for a landing
+    pub max_flight_time: Seconds,
+}
+
+impl Default for FitOptions {
+    fn default() -> Self {
+        Self {
+            fit_drag: false,
+            dt: Seconds(0.001),
+            max_iterations: 50,
+            max_flight_time: Seconds(600.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Prediction {
+    pub launch_position: Vector2,
+    pub launch_velocity: Vector2,
+    pub drag_coefficient: f64,
+    /// Root mean square distance between the fitted model and the observations
+    pub rms_residual: f64,
+    pub iterations: usize,
+    pub landing_time: f64,
+    pub landing_point: Vector2,
+}
+
+/// Read `t,x,y` rows. Blank lines, `#` comments and a non-numeric header are
+/// skipped.
+pub fn read_observations_csv(path: &Path) -> Result<Vec<Observation>, anyhow::Error> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("reading {:?}", path))?;
+    let mut observations = vec![];
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+        let values: Result<Vec<f64>, _> = fields.iter().map(|f| f.parse::<f64>()).collect();
+        match values {
+            Ok(values) if values.len() == 3 => observations.push(Observation {
+                t: values[0],
+                position: Vector2::new(values[1], values[2]),
+            }),
+            Ok(_) => bail!("line {}: expected 3 columns (t,x,y), got {}", idx + 1, fields.len()),
+            Err(_) if observations.is_empty() && idx == 0 => continue, // header
+            Err(e) => bail!("line {}: {}", idx + 1, e),
+        }
+    }
+
+    Ok(observations)
+}
+
+/// Fit the launch of `template` (its environment and aerodynamics are kept)
+/// to `observations` by least squares, then fly the fitted launch to the
+/// ground.
+pub fn predict(
+    observations: &[Observation],
+    template: &Projectile,
+    options: &FitOptions,
+) -> Result<Prediction, anyhow::Error> {
+    if observations.len() < 3 {
+        bail!("need at least 3 observations, got {}", observations.len());
+    }
+    if options.fit_drag && template.environment.atmosphere == Atmosphere::Vacuum {
+        bail!("cannot fit a drag coefficient without an atmosphere");
+    }
+
+    let mut observations = observations.to_vec();
+    observations.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+    let mut params = initial_guess(&observations, template)?;
+    if options.fit_drag {
+        params.push(template.aerodynamics.drag_coefficient);
+    }
+
+    let iterations = levenberg_marquardt(&mut params, &observations, template, options);
+    let residuals = residuals(&params, &observations, template, options.dt.0);
+    let rms_residual = (residuals.iter().map(|r| r * r).sum::<f64>() / observations.len() as f64).sqrt();
+
+    let projectile = launch(&params, template);
+    let (landing_time, landing_point) = find_landing(projectile, options)?;
+
+    Ok(Prediction {
+        launch_position: projectile_position(&params),
+        launch_velocity: Vector2::new(params[2], params[3]),
+        drag_coefficient: params.get(4).copied().unwrap_or(template.aerodynamics.drag_coefficient),
+        rms_residual,
+        iterations,
+        landing_time,
+        landing_point,
+    })
+}
+
+fn projectile_position(params: &[f64]) -> Vector2 {
+    Vector2::new(params[0], params[1])
+}
+
+// params = [x0, y0, vx0, vy0, (drag coefficient)]
+fn launch(params: &[f64], template: &Projectile) -> Projectile {
+    let mut projectile = template.clone();
+    projectile.position = projectile_position(params);
+    projectile.acceleration = Vector2::default();
+    projectile.time = 0.0;
+    if let Some(cd) = params.get(4) {
+        projectile.aerodynamics.drag_coefficient = *cd;
+    }
+    projectile.fire(&Vector2::new(params[2], params[3]));
+    projectile
+}
+
+// Drag-free ballistic fit, solved directly with linear regression:
+// x = x0 + vx t and y - g t^2 / 2 = y0 + vy t
+fn initial_guess(observations: &[Observation], template: &Projectile) -> Result<Vec<f64>, anyhow::Error> {
+    let g = template.environment.gravity.acceleration(0.0);
+    let n = observations.len() as f64;
+    let mean_t = observations.iter().map(|o| o.t).sum::<f64>() / n;
+    let var_t: f64 = observations.iter().map(|o| (o.t - mean_t).powi(2)).sum();
+    if var_t <= f64::EPSILON {
+        bail!("observations must span more than one instant");
+    }
+
+    let fit = |value: &dyn Fn(&Observation) -> f64| {
+        let mean = observations.iter().map(value).sum::<f64>() / n;
+        let slope = observations
+            .iter()
+            .map(|o| (o.t - mean_t) * (value(o) - mean))
+            .sum::<f64>()
+            / var_t;
+        (mean - slope * mean_t, slope)
+    };
+
+    let (x0, vx) = fit(&|o| o.position.x - 0.5 * g.x * o.t * o.t);
+    let (y0, vy) = fit(&|o| o.position.y - 0.5 * g.y * o.t * o.t);
+    Ok(vec![x0, y0, vx, vy])
+}
+
+// Model minus observation for x and y of every sample. Observations must be
+// sorted by time.
+fn residuals(params: &[f64], observations: &[Observation], template: &Projectile, dt: f64) -> Vec<f64> {
+    let mut projectile = launch(params, template);
+    let mut t = 0.0;
+    let mut previous = projectile.position;
+    let mut out = Vec::with_capacity(observations.len() * 2);
+
+    for observation in observations {
+        while t < observation.t {
+            previous = projectile.position;
+            projectile.update(&dt);
+            t += dt;
+        }
+
+        // Interpolate inside the step that crossed the sample time
+        let frac = if t > 0.0 { 1.0 - (t - observation.t) / dt } else { 1.0 };
+        let model = previous + (projectile.position - previous) * frac.clamp(0.0, 1.0);
+        out.push(model.x - observation.position.x);
+        out.push(model.y - observation.position.y);
+    }
+
+    out
+}
+
+fn cost(residuals: &[f64]) -> f64 {
+    residuals.iter().map(|r| r * r).sum()
+}
+
+// Returns the number of iterations taken
+fn levenberg_marquardt(
+    params: &mut Vec<f64>,
+    observations: &[Observation],
+    template: &Projectile,
+    options: &FitOptions,
+) -> usize {
+    let n = params.len();
+    let mut lambda = 1e-3;
+    let mut r = residuals(params, observations, template, options.dt.0);
+    let mut current_cost = cost(&r);
+
+    for iteration in 0..options.max_iterations {
+        // Forward difference Jacobian, one column per parameter
+        let mut jacobian = vec![vec![0.0; r.len()]; n];
+        for (p, column) in jacobian.iter_mut().enumerate() {
+            let h = 1e-6 * params[p].abs().max(1.0);
+            let mut shifted = params.clone();
+            shifted[p] += h;
+            let r_shifted = residuals(&shifted, observations, template, options.dt.0);
+            for (j, value) in column.iter_mut().enumerate() {
+                *value = (r_shifted[j] - r[j]) / h;
+            }
+        }
+
+        let mut jtj = vec![vec![0.0; n]; n];
+        let mut jtr = vec![0.0; n];
+        for a in 0..n {
+            for b in 0..n {
+                jtj[a][b] = (0..r.len()).map(|k| jacobian[a][k] * jacobian[b][k]).sum();
+            }
+            jtr[a] = (0..r.len()).map(|k| jacobian[a][k] * r[k]).sum();
+        }
+
+        let mut improved = false;
+        while lambda < 1e12 {
+            let mut damped = jtj.clone();
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += lambda * jtj[i][i].max(1e-12);
+            }
+            let rhs: Vec<f64> = jtr.iter().map(|v| -v).collect();
+            let Some(step) = solve(damped, rhs) else {
+                lambda *= 10.0;
+                continue;
+            };
+
+            let mut candidate: Vec<f64> = params.iter().zip(&step).map(|(p, s)| p + s).collect();
+            if let Some(cd) = candidate.get_mut(4) {
+                *cd = cd.max(0.0);
+            }
+            let candidate_r = residuals(&candidate, observations, template, options.dt.0);
+            let candidate_cost = cost(&candidate_r);
+
+            if candidate_cost < current_cost {
+                let relative_change = (current_cost - candidate_cost) / current_cost.max(1e-300);
+                let step_size = params
+                    .iter()
+                    .zip(&candidate)
+                    .map(|(old, new)| (new - old).abs())
+                    .fold(0.0, f64::max);
+                *params = candidate;
+                r = candidate_r;
+                current_cost = candidate_cost;
+                lambda = (lambda / 10.0).max(1e-12);
+                improved = true;
+                if relative_change < 1e-10 || step_size < 1e-9 {
+                    return iteration + 1;
+                }
+                break;
+            }
+            lambda *= 10.0;
+        }
+
+        if !improved {
+            return iteration + 1;
+        }
+    }
+
+    options.max_iterations
+}
+
+// Gaussian elimination with partial pivoting. None if the system is singular.
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-300 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..n {
+            let factor = a[row][col] / a[col][col];
+            let (above, below) = a.split_at_mut(row);
+            for (value, pivot_value) in below[0][col..].iter_mut().zip(&above[col][col..]) {
+                *value -= factor * pivot_value;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+// First time the projectile comes down onto the ground, bouncing or coming
+// straight to rest. Bounces off walls on the way don't count.
+fn find_landing(mut projectile: Projectile, options: &FitOptions) -> Result<(f64, Vector2), anyhow::Error> {
+    while projectile.time < options.max_flight_time.0 {
+        projectile.update(&options.dt.0);
+        if !projectile.on_ground() {
+            continue;
+        }
+        return Ok(match projectile.last_impact.filter(|impact| impact.is_ground()) {
+            Some(impact) => (impact.t, impact.point),
+            // Too slow to bounce, so there is no impact. It touched down
+            // during this step.
+            None => (projectile.time, Vector2::new(projectile.position.x, 0.0)),
+        });
+    }
+    bail!("projectile did not land within {}", options.max_flight_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::collision::Segment;
+
+    // Samples of a vacuum launch from (1, 10) at (5, 8) m/s, straight off the parabola
+    fn observations() -> Vec<Observation> {
+        (1..=10)
+            .map(|i| {
+                let t = i as f64 * 0.1;
+                let position = Vector2::new(1.0 + 5.0 * t, 10.0 + 8.0 * t - 0.5 * 9.81 * t * t);
+                Observation { t, position }
+            })
+            .collect()
+    }
+
+    // Lands where 10 + 8t - 4.905t^2 = 0
+    fn landing_time() -> f64 {
+        (8.0 + (64.0_f64 + 4.0 * 4.905 * 10.0).sqrt()) / 9.81
+    }
+
+    fn close(a: Vector2, b: Vector2, tolerance: f64) -> bool {
+        (a - b).length() < tolerance
+    }
+
+    #[test]
+    fn recovers_a_known_launch() {
+        let result = predict(&observations(), &Projectile::default(), &FitOptions::default()).unwrap();
+
+        assert!(close(result.launch_position, Vector2::new(1.0, 10.0), 0.01), "{:?}", result);
+        assert!(close(result.launch_velocity, Vector2::new(5.0, 8.0), 0.01), "{:?}", result);
+        assert!(result.rms_residual < 0.01);
+        assert!((result.landing_time - landing_time()).abs() < 0.01, "{:?}", result);
+        assert!((result.landing_point.x - (1.0 + 5.0 * landing_time())).abs() < 0.05);
+        assert_eq!(result.landing_point.y, 0.0);
+    }
+
+    #[test]
+    fn walls_on_the_way_down_are_not_the_landing() {
+        // Hit at x = 10 around t = 1.8 s, well before the ground
+        let mut template = Projectile::default();
+        template.environment.obstacles = vec![Segment::new(Vector2::new(10.0, 0.0), Vector2::new(10.0, 20.0))];
+        let result = predict(&observations(), &template, &FitOptions::default()).unwrap();
+
+        assert_eq!(result.landing_point.y, 0.0);
+        assert!(result.landing_point.x < 10.0, "{:?}", result);
+        assert!(result.landing_time > 1.8, "{:?}", result);
+    }
+
+    #[test]
+    fn slow_arrivals_that_come_to_rest_still_land() {
+        // Lobbed from 10 cm, touching down at under 1.5 m/s: too slow to bounce
+        let observations: Vec<Observation> = (1..=8)
+            .map(|i| {
+                let t = i as f64 * 0.02;
+                let position = Vector2::new(2.0 * t, 0.1 + 0.5 * t - 0.5 * 9.81 * t * t);
+                Observation { t, position }
+            })
+            .collect();
+        let result = predict(&observations, &Projectile::default(), &FitOptions::default()).unwrap();
+
+        let landing_time = (0.5 + (0.25_f64 + 2.0 * 9.81 * 0.1).sqrt()) / 9.81;
+        assert!((result.landing_time - landing_time).abs() < 0.005, "{:?}", result);
+        assert!((result.landing_point.x - 2.0 * landing_time).abs() < 0.01, "{:?}", result);
+        assert_eq!(result.landing_point.y, 0.0);
+    }
+
+    #[test]
+    fn too_few_observations() {
+        let observations = &observations()[..2];
+        assert!(predict(observations, &Projectile::default(), &FitOptions::default()).is_err());
+    }
+
+    fn write_csv(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("trajectory-{}-{}.csv", name, std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn reads_csv_with_header_comments_and_blanks() {
+        let path = write_csv("good", "t,x,y\n# launch\n0.1, 1.5, 10.75\n\n0.2,2.0,11.4\n");
+        let observations = read_observations_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            observations,
+            [
+                Observation { t: 0.1, position: Vector2::new(1.5, 10.75) },
+                Observation { t: 0.2, position: Vector2::new(2.0, 11.4) },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_bad_csv_rows() {
+        let path = write_csv("columns", "0.1,1.5\n");
+        let error = read_observations_csv(&path).unwrap_err().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("line 1"), "{}", error);
+
+        let path = write_csv("number", "0.1,1.5,10\n0.2,x,11\n");
+        let error = read_observations_csv(&path).unwrap_err().to_string();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("line 2"), "{}", error);
+    }
+}
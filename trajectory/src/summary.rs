@@ -0,0 +1,181 @@
+use serde::Serialize;
+
+use crate::{diagnostics::StepSample, Vector2};
+
+/// Headline numbers for one simulated flight
+#[derive(Debug, Clone, Serialize)]
+pub struct FlightSummary {
+    pub launch_position: Vector2,
+    /// Horizontal distance from launch to the first contact with the ground
+    pub range: Option<f64>,
+    /// Highest point reached
+    pub apex: Vector2,
+    /// Time of the first contact with the ground
+    pub flight_time: Option<f64>,
+    /// Number of times the projectile bounced off the ground. Walls don't count.
+    pub bounces: usize,
+    pub final_position: Vector2,
+    pub final_velocity: Vector2,
+    pub duration: f64,
+    /// Closest approach to the target of a guided flight
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub miss_distance: Option<f64>,
+}
+
+impl FlightSummary {
+    /// Summarise the flight from `launch`, the state it was fired in, through
+    /// `samples`. With no samples the flight ends where it started.
+    pub fn from_samples(launch: StepSample, samples: &[StepSample]) -> Self {
+        let launch_position = launch.position;
+        let mut apex = launch_position;
+        for sample in samples {
+            if sample.position.y > apex.y {
+                apex = sample.position;
+            }
+        }
+        // Steps rarely land exactly on the apex, refine it from the state just
+        // before vertical velocity changes sign
+        for pair in samples.windows(2) {
+            let (before, after) = (&pair[0], &pair[1]);
+            if before.velocity.y > 0.0 && after.velocity.y <= 0.0 && before.acceleration.y < 0.0 {
+                let rise_time = -before.velocity.y / before.acceleration.y;
+                let peak = before.position
+                    + Vector2::new(before.velocity.x, 0.5 * before.velocity.y) * rise_time;
+                if peak.y > apex.y {
+                    apex = peak;
+                }
+            }
+        }
+
+        let mut impacts = samples.iter().filter_map(|s| s.impact).filter(|impact| impact.is_ground());
+        let first_contact = impacts.next();
+        let bounces = first_contact.map_or(0, |_| 1 + impacts.count());
+
+        let last = samples.last().copied().unwrap_or(launch);
+        Self {
+            launch_position,
+            range: first_contact.map(|impact| impact.point.x - launch_position.x),
+            apex,
+            flight_time: first_contact.map(|impact| impact.t),
+            bounces,
+            final_position: last.position,
+            final_velocity: last.velocity,
+            duration: last.t,
+            miss_distance: None,
+        }
+    }
+
+    pub fn to_human(&self) -> String {
+        let optional = |value: Option<f64>, unit: &str| match value {
+            Some(v) => format!("{:.3} {}", v, unit),
+            None => "did not land".to_string(),
+        };
+
+        let mut lines = vec![
+            format!(
+                "Launch position: ({:.3}, {:.3}) m",
+                self.launch_position.x, self.launch_position.y
+            ),
+            format!("Range:           {}", optional(self.range, "m")),
+            format!("Apex:            ({:.3}, {:.3}) m", self.apex.x, self.apex.y),
+            format!("Flight time:     {}", optional(self.flight_time, "s")),
+            format!("Bounces:         {}", self.bounces),
+            format!(
+                "Final position:  ({:.3}, {:.3}) m after {:.2} s",
+                self.final_position.x, self.final_position.y, self.duration
+            ),
+            format!(
+                "Final velocity:  ({:.3}, {:.3}) m/s",
+                self.final_velocity.x, self.final_velocity.y
+            ),
+        ];
+        if let Some(miss) = self.miss_distance {
+            lines.push(format!("Miss distance:   {:.3} m", miss));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{collision::Segment, Projectile};
+
+    const G: f64 = 9.81;
+
+    fn fly(mut projectile: Projectile, duration: f64) -> Vec<StepSample> {
+        let dt = 0.001;
+        let steps = (duration / dt).round() as usize;
+        (1..=steps)
+            .map(|_| {
+                projectile.update(&dt);
+                StepSample::capture(projectile.time, &projectile)
+            })
+            .collect()
+    }
+
+    fn summarise(projectile: Projectile, duration: f64) -> FlightSummary {
+        let launch = StepSample::capture(0.0, &projectile);
+        FlightSummary::from_samples(launch, &fly(projectile, duration))
+    }
+
+    fn launched(position: Vector2, velocity: Vector2) -> Projectile {
+        let mut projectile = Projectile::new(&position);
+        projectile.fire(&velocity);
+        projectile
+    }
+
+    #[test]
+    fn range_apex_and_flight_time_of_a_vacuum_shot() {
+        let launch = Vector2::new(1.0, 0.0);
+        let summary = summarise(launched(launch, Vector2::new(10.0, 10.0)), 3.0);
+
+        // Lands after 2 vy / g, 10.19 m out, peaking at vy^2 / 2g
+        let flight_time = 2.0 * 10.0 / G;
+        assert!((summary.flight_time.unwrap() - flight_time).abs() < 0.01, "{:?}", summary);
+        assert!((summary.range.unwrap() - 10.0 * flight_time).abs() < 0.05, "{:?}", summary);
+        assert!((summary.apex.y - 100.0 / (2.0 * G)).abs() < 0.01);
+        assert!((summary.apex.x - 1.0 - 10.0 * 10.0 / G).abs() < 0.05);
+        assert!(summary.bounces >= 1);
+        assert!((summary.duration - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn walls_are_not_landings_or_bounces() {
+        let launch = Vector2::new(0.0, 10.0);
+        let mut projectile = launched(launch, Vector2::new(10.0, 0.0));
+        projectile.environment.obstacles = vec![Segment::new(Vector2::new(8.0, 0.0), Vector2::new(8.0, 20.0))];
+        let start = StepSample::capture(0.0, &projectile);
+        let samples = fly(projectile, 3.0);
+        assert!(samples.iter().any(|s| s.impact.is_some_and(|impact| !impact.is_ground())));
+
+        let summary = FlightSummary::from_samples(start, &samples);
+        let fall_time = (2.0 * 10.0 / G).sqrt();
+        assert!((summary.flight_time.unwrap() - fall_time).abs() < 0.01, "{:?}", summary);
+        // Bounced back off the wall at x = 8 before coming down
+        let landing_x = 8.0 - 2.5 * (fall_time - 0.8);
+        assert!((summary.range.unwrap() - landing_x).abs() < 0.05, "{:?}", summary);
+        let ground_bounces = samples.iter().filter_map(|s| s.impact).filter(|i| i.is_ground()).count();
+        assert_eq!(summary.bounces, ground_bounces);
+    }
+
+    #[test]
+    fn no_samples_ends_at_the_launch() {
+        let launch = Vector2::new(3.0, 7.0);
+        let summary = summarise(launched(launch, Vector2::new(4.0, 5.0)), 0.0);
+
+        assert_eq!(summary.final_position, launch);
+        assert_eq!(summary.final_velocity, Vector2::new(4.0, 5.0));
+        assert_eq!(summary.apex, launch);
+        assert_eq!((summary.duration, summary.range, summary.bounces), (0.0, None, 0));
+    }
+
+    #[test]
+    fn no_landing_within_the_flight() {
+        let launch = Vector2::new(0.0, 1000.0);
+        let summary = summarise(launched(launch, Vector2::new(5.0, 0.0)), 1.0);
+
+        assert_eq!((summary.range, summary.flight_time, summary.bounces), (None, None, 0));
+        assert!(summary.to_human().contains("did not land"));
+    }
+}
@@ -0,0 +1,286 @@
+use std::{
+    fmt,
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use serde::Serialize;
+
+// Declares an SI quantity wrapping an `f64`, with the suffixes it parses from
+// and their factor to the SI unit. Bare numbers parse as SI.
+macro_rules! quantity {
+    ($(#[$doc:meta])* $name:ident, $unit:literal, [$(($suffix:literal, $factor:expr)),* $(,)?]) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize)]
+        pub struct $name(pub f64);
+
+        impl $name {
+            pub const SUFFIXES: &'static [(&'static str, f64)] = &[$(($suffix, $factor)),*];
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}{}", self.0, $unit)
+            }
+        }
+
+        impl From<$name> for f64 {
+            fn from(quantity: $name) -> f64 {
+                quantity.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+
+            fn from_str(value: &str) -> Result<Self, String> {
+                parse_quantity(value, Self::SUFFIXES, true).map($name)
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+            fn add(self, other: $name) -> $name {
+                $name(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+            fn sub(self, other: $name) -> $name {
+                $name(self.0 - other.0)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        impl Mul<f64> for $name {
+            type Output = $name;
+            fn mul(self, scale: f64) -> $name {
+                $name(self.0 * scale)
+            }
+        }
+    };
+}
+
+quantity!(
+    /// Length in metres
+    Metres, "m", [
+        ("m", 1.0), ("cm", 0.01), ("mm", 0.001), ("km", 1000.0),
+        ("in", 0.0254), ("ft", 0.3048), ("yd", 0.9144), ("mi", 1609.344),
+    ]
+);
+
+quantity!(
+    /// Time in seconds
+    Seconds, "s", [("s", 1.0), ("ms", 0.001), ("min", 60.0), ("h", 3600.0)]
+);
+
+quantity!(
+    /// Speed in metres per second
+    MetresPerSecond, "m/s", [
+        ("m/s", 1.0), ("km/h", 1.0 / 3.6), ("kph", 1.0 / 3.6),
+        ("mph", 0.447_04), ("ft/s", 0.3048), ("kn", 1852.0 / 3600.0),
+    ]
+);
+
+quantity!(
+    /// Acceleration in metres per second squared
+    MetresPerSecondSquared, "m/s^2", [
+        ("m/s^2", 1.0), ("m/s²", 1.0), ("ft/s^2", 0.3048), ("g", 9.806_65),
+    ]
+);
+
+quantity!(
+    /// Mass in kilograms
+    Kilograms, "kg", [("kg", 1.0), ("g", 0.001), ("lb", 0.453_592_37), ("oz", 0.028_349_523_125)]
+);
+
+impl Div<Seconds> for Metres {
+    type Output = MetresPerSecond;
+    fn div(self, time: Seconds) -> MetresPerSecond {
+        MetresPerSecond(self.0 / time.0)
+    }
+}
+
+impl Mul<Seconds> for MetresPerSecond {
+    type Output = Metres;
+    fn mul(self, time: Seconds) -> Metres {
+        Metres(self.0 * time.0)
+    }
+}
+
+/// A plane angle. Built from degrees or radians explicitly so the two can't
+/// be mixed up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Angle {
+    radians: f64,
+}
+
+impl Angle {
+    pub const SUFFIXES: &'static [(&'static str, f64)] = &[
+        ("deg", std::f64::consts::PI / 180.0),
+        ("°", std::f64::consts::PI / 180.0),
+        ("rad", 1.0),
+    ];
+
+    pub fn degrees(degrees: f64) -> Self {
+        Self { radians: degrees.to_radians() }
+    }
+
+    pub fn radians(radians: f64) -> Self {
+        Self { radians }
+    }
+
+    pub fn to_degrees(self) -> f64 {
+        self.radians.to_degrees()
+    }
+
+    pub fn to_radians(self) -> f64 {
+        self.radians
+    }
+}
+
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}deg", self.to_degrees())
+    }
+}
+
+impl FromStr for Angle {
+    type Err = String;
+
+    // A bare number is ambiguous, so angles always need a unit
+    fn from_str(value: &str) -> Result<Self, String> {
+        parse_quantity(value, Self::SUFFIXES, false).map(Angle::radians)
+    }
+}
+
+/// Parse a quantity that has to be finite and not negative, for use as a
+/// clap `value_parser`
+pub fn non_negative<Q>(value: &str) -> Result<Q, String>
+where
+    Q: FromStr<Err = String> + Into<f64> + Copy,
+{
+    let quantity: Q = value.parse()?;
+    let si: f64 = quantity.into();
+    match si.is_finite() && si >= 0.0 {
+        true => Ok(quantity),
+        false => Err(format!("{:?} must not be negative", value)),
+    }
+}
+
+/// Parse a quantity that has to be finite and above zero, for use as a clap
+/// `value_parser`
+pub fn positive<Q>(value: &str) -> Result<Q, String>
+where
+    Q: FromStr<Err = String> + Into<f64> + Copy,
+{
+    let quantity: Q = value.parse()?;
+    let si: f64 = quantity.into();
+    match si.is_finite() && si > 0.0 {
+        true => Ok(quantity),
+        false => Err(format!("{:?} must be above zero", value)),
+    }
+}
+
+// Split `value` into a number and a unit suffix from `suffixes`, returning
+// the value in SI units
+fn parse_quantity(value: &str, suffixes: &[(&str, f64)], allow_bare: bool) -> Result<f64, String> {
+    let value = value.trim();
+    // Longest prefix that is a number, so exponents like 1e3m still parse
+    let (number, unit) = value
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain([value.len()])
+        .rev()
+        .find_map(|i| value[..i].parse::<f64>().ok().map(|n| (n, value[i..].trim())))
+        .ok_or_else(|| format!("expected a number with a unit, got {:?}", value))?;
+
+    let units = || suffixes.iter().map(|(s, _)| *s).collect::<Vec<_>>().join(", ");
+    if unit.is_empty() {
+        return match allow_bare {
+            true => Ok(number),
+            false => Err(format!("{:?} needs a unit, one of: {}", value, units())),
+        };
+    }
+    suffixes
+        .iter()
+        .find(|(suffix, _)| suffix.eq_ignore_ascii_case(unit))
+        .map(|(_, factor)| number * factor)
+        .ok_or_else(|| format!("unknown unit {:?}, expected one of: {}", unit, units()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn suffixes_convert_to_si() {
+        assert_eq!("12.5m".parse(), Ok(Metres(12.5)));
+        assert!(close("3 ft".parse::<Metres>().unwrap().0, 0.9144));
+        assert!(close("2km".parse::<Metres>().unwrap().0, 2000.0));
+        assert!(close("250ms".parse::<Seconds>().unwrap().0, 0.25));
+        assert!(close("2min".parse::<Seconds>().unwrap().0, 120.0));
+        assert!(close("36km/h".parse::<MetresPerSecond>().unwrap().0, 10.0));
+        assert!(close("1lb".parse::<Kilograms>().unwrap().0, 0.453_592_37));
+        assert!(close("4g".parse::<MetresPerSecondSquared>().unwrap().0, 39.2266));
+        assert!(close("40 m/s^2".parse::<MetresPerSecondSquared>().unwrap().0, 40.0));
+        assert!(close("45deg".parse::<Angle>().unwrap().to_radians(), std::f64::consts::FRAC_PI_4));
+        assert!(close("1.5rad".parse::<Angle>().unwrap().to_radians(), 1.5));
+    }
+
+    #[test]
+    fn units_are_case_insensitive_and_bare_numbers_are_si() {
+        assert!(close("5KM".parse::<Metres>().unwrap().0, 5000.0));
+        assert_eq!(" 7 ".parse(), Ok(Seconds(7.0)));
+        assert_eq!("-3".parse(), Ok(Metres(-3.0)));
+    }
+
+    #[test]
+    fn exponents_are_part_of_the_number() {
+        assert!(close("1e3m".parse::<Metres>().unwrap().0, 1000.0));
+        assert!(close("2.5e-3s".parse::<Seconds>().unwrap().0, 0.0025));
+        assert!(close("1E2km".parse::<Metres>().unwrap().0, 100_000.0));
+    }
+
+    #[test]
+    fn bare_angles_are_rejected() {
+        let error = "45".parse::<Angle>().unwrap_err();
+        assert!(error.contains("needs a unit"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_units_and_garbage_are_rejected() {
+        let error = "3 furlongs".parse::<Metres>().unwrap_err();
+        assert!(error.contains("unknown unit \"furlongs\""), "{}", error);
+        assert!("10kg".parse::<Seconds>().is_err());
+        assert!("fast".parse::<MetresPerSecond>().is_err());
+        assert!("".parse::<Metres>().is_err());
+    }
+
+    #[test]
+    fn positive_rejects_zero_and_below() {
+        assert_eq!(positive::<Seconds>("10ms"), Ok(Seconds(0.01)));
+        assert!(positive::<Seconds>("0").is_err());
+        assert!(positive::<Seconds>("-1ms").is_err());
+        assert!(positive::<Seconds>("inf").is_err());
+    }
+
+    #[test]
+    fn non_negative_allows_zero() {
+        assert_eq!(non_negative::<Seconds>("0s"), Ok(Seconds(0.0)));
+        assert_eq!(non_negative::<Metres>("1.5"), Ok(Metres(1.5)));
+        assert!(non_negative::<Seconds>("-2min").is_err());
+        assert!(non_negative::<Metres>("NaN").is_err());
+    }
+}
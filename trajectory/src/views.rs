@@ -0,0 +1,113 @@
+use clap::ValueEnum;
+
+use crate::diagnostics::{self, StepSample};
+
+/// Extra points drawn over the projectile path, e.g. filter estimates
+#[derive(Debug, Clone, Copy)]
+pub struct Overlay<'a> {
+    pub name: &'a str,
+    pub data: &'a [(f64, f64)],
+    /// Draw as unconnected points instead of a line
+    pub scatter: bool,
+}
+
+/// Which chart fills the main panel of the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum View {
+    #[default]
+    Path,
+    X,
+    Y,
+    Vx,
+    Vy,
+    Speed,
+    Acceleration,
+}
+
+impl View {
+    pub const ALL: [View; 7] = [
+        View::Path,
+        View::X,
+        View::Y,
+        View::Vx,
+        View::Vy,
+        View::Speed,
+        View::Acceleration,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            View::Path => "y(x)",
+            View::X => "x(t)",
+            View::Y => "y(t)",
+            View::Vx => "vx(t)",
+            View::Vy => "vy(t)",
+            View::Speed => "speed(t)",
+            View::Acceleration => "a(t)",
+        }
+    }
+
+    /// Unit label for the value axis of the time charts
+    pub fn unit(&self) -> &'static str {
+        match self {
+            View::Path | View::X | View::Y => "m",
+            View::Vx | View::Vy | View::Speed => "m/s",
+            View::Acceleration => "m/s^2",
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        View::ALL.iter().position(|v| v == self).unwrap_or(0)
+    }
+
+    pub fn next(&self) -> View {
+        View::ALL[(self.index() + 1) % View::ALL.len()]
+    }
+
+    pub fn previous(&self) -> View {
+        View::ALL[(self.index() + View::ALL.len() - 1) % View::ALL.len()]
+    }
+
+    /// Named series to plot against time. Empty for `View::Path`, which is
+    /// drawn by `make_chart` instead.
+    pub fn series(&self, samples: &[StepSample]) -> Vec<(&'static str, Vec<(f64, f64)>)> {
+        match self {
+            View::Path => vec![],
+            View::X => vec![("x", diagnostics::series(samples, |s| s.position.x))],
+            View::Y => vec![("y", diagnostics::series(samples, |s| s.position.y))],
+            View::Vx => vec![("vx", diagnostics::series(samples, |s| s.velocity.x))],
+            View::Vy => vec![("vy", diagnostics::series(samples, |s| s.velocity.y))],
+            View::Speed => vec![("|v|", diagnostics::series(samples, |s| s.velocity.length()))],
+            View::Acceleration => vec![
+                ("ax", diagnostics::series(samples, |s| s.acceleration.x)),
+                ("ay", diagnostics::series(samples, |s| s.acceleration.y)),
+                ("|a|", diagnostics::series(samples, |s| s.acceleration.length())),
+            ],
+        }
+    }
+}
+
+// Axis bounds that always include the default view and grow to fit the data
+pub fn chart_bounds(data: &[(f64, f64)]) -> ([f64; 2], [f64; 2]) {
+    // Leave a little headroom so data on the edge is not hidden by the frame
+    let min_x = data.iter().fold(-1.0_f64, |acc, p| acc.min(p.0 - 1.0));
+    let min_y = data.iter().fold(-1.0_f64, |acc, p| acc.min(p.1 - 1.0));
+    let max_x = data.iter().fold(15.0_f64, |acc, p| acc.max(p.0 * 1.05));
+    let max_y = data.iter().fold(30.0_f64, |acc, p| acc.max(p.1 * 1.05));
+    ([min_x, max_x], [min_y, max_y])
+}
+
+// Tight bounds around every series, padded so flat lines stay visible
+pub fn series_bounds(series: &[&[(f64, f64)]]) -> ([f64; 2], [f64; 2]) {
+    let mut x = [f64::MAX, f64::MIN];
+    let mut y = [f64::MAX, f64::MIN];
+    for (px, py) in series.iter().flat_map(|data| data.iter()) {
+        x = [x[0].min(*px), x[1].max(*px)];
+        y = [y[0].min(*py), y[1].max(*py)];
+    }
+    if x[0] > x[1] {
+        return ([0.0, 1.0], [0.0, 1.0]);
+    }
+    let pad = ((y[1] - y[0]) * 0.05).max(1e-3);
+    (x, [y[0] - pad, y[1] + pad])
+}